@@ -1,4 +1,4 @@
-use shiplift::{Docker, Exec, ExecContainerOptions, ExecResizeOptions};
+use shiplift::{Docker, ExecContainerOptions, ExecResizeOptions};
 use std::env;
 
 #[tokio::main]
@@ -19,7 +19,12 @@ async fn main() {
         .attach_stdout(true)
         .attach_stderr(true)
         .build();
-    let exec = Exec::create(&docker, &id, &exec_opts).await.unwrap();
+    let exec = docker
+        .containers()
+        .get(&id)
+        .exec_create(&exec_opts)
+        .await
+        .unwrap();
 
     // Resize its window with given parameters
     let resize_opts = ExecResizeOptions::builder()
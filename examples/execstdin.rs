@@ -0,0 +1,41 @@
+use futures::{AsyncWriteExt, StreamExt};
+use shiplift::{tty::TtyChunk, Docker, Exec, ExecContainerOptions};
+use std::{env, str::from_utf8};
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let docker = Docker::new();
+    let id = env::args()
+        .nth(1)
+        .expect("You need to specify a container id");
+
+    let options = ExecContainerOptions::builder()
+        .cmd(vec!["cat"])
+        .attach_stdin(true)
+        .attach_stdout(true)
+        .attach_stderr(true)
+        .build();
+
+    let exec = Exec::create(&docker, &id, &options).await?;
+    let (mut reader, mut writer) = exec.start_duplex().await?.split();
+
+    writer.write_all(b"echoed through cat\n").await?;
+    writer.close().await?;
+
+    while let Some(tty_result) = reader.next().await {
+        match tty_result {
+            Ok(chunk) => print_chunk(chunk),
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn print_chunk(chunk: TtyChunk) {
+    match chunk {
+        TtyChunk::StdOut(bytes) => println!("Stdout: {}", from_utf8(&bytes).unwrap()),
+        TtyChunk::StdErr(bytes) => eprintln!("Stderr: {}", from_utf8(&bytes).unwrap()),
+        TtyChunk::StdIn(_) => unreachable!(),
+    }
+}
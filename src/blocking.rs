@@ -0,0 +1,638 @@
+//! A blocking interface for programs that don't run an async runtime.
+//!
+//! Each [Docker](crate::blocking::Docker) owns a single threaded `tokio` runtime which is used to
+//! drive the requests of the async [Docker](crate::Docker) to completion. Streams are exposed as
+//! `Iterator`s that block until the next item arrives.
+//!
+//! # examples
+//!
+//! ```no_run
+//! let docker = shiplift::blocking::Docker::new().unwrap();
+//!
+//! for container in docker.containers().list(&Default::default()).unwrap() {
+//!     println!("{}", container.id);
+//! }
+//! ```
+
+use std::{path::Path, pin::Pin, time::Duration};
+
+use futures_util::{stream::Stream, StreamExt};
+use tokio::runtime::{Builder, Runtime};
+
+use crate::{
+    container::{
+        self, ContainerCreateInfo, ContainerDetails, ContainerInfo, ContainerListOptions,
+        ContainerOptions, Exit, ExportSummary, LogsOptions, RmContainerOptions, Stats, Top,
+        TopOptions,
+    },
+    docker::{self, AuthInfo, DataUsage, Event, EventsOptions, Info, ReclaimableReport, Version},
+    errors::Result,
+    image::{
+        self, History, ImageBuildChunk, ImageDetails, ImageInfo, ImageListOptions, PullOptions,
+        RegistryAuth, SearchResult, Status, TagOptions,
+    },
+    network::{
+        self, ContainerConnectionOptions, NetworkCreateInfo, NetworkCreateOptions, NetworkDetails,
+        NetworkListOptions,
+    },
+    tty::TtyChunk,
+    volume::{self, VolumeCreateInfo, VolumeCreateOptions, VolumeInfo},
+    Uri,
+};
+
+/// Blocking counterpart of [Docker](crate::Docker)
+pub struct Docker {
+    docker: docker::Docker,
+    runtime: Runtime,
+}
+
+impl Docker {
+    /// constructs a new Docker instance for a docker host listening at a url specified by an env var `DOCKER_HOST`,
+    /// falling back on unix:///var/run/docker.sock
+    pub fn new() -> Result<Docker> {
+        Docker::with(docker::Docker::new())
+    }
+
+    /// Creates a new docker instance for a docker host
+    /// listening on a given Unix socket.
+    #[cfg(feature = "unix-socket")]
+    pub fn unix<S>(socket_path: S) -> Result<Docker>
+    where
+        S: Into<String>,
+    {
+        Docker::with(docker::Docker::unix(socket_path))
+    }
+
+    /// Creates a new docker instance for the docker host reachable over ssh at `destination`, a
+    /// `host` or `user@host`
+    #[cfg(feature = "ssh")]
+    pub fn ssh<S>(
+        destination: S,
+        port: Option<u16>,
+    ) -> Result<Docker>
+    where
+        S: Into<String>,
+    {
+        Docker::with(docker::Docker::ssh(destination, port))
+    }
+
+    /// constructs a new Docker instance for docker host listening at the given host url
+    pub fn host(host: Uri) -> Result<Docker> {
+        Docker::with(docker::Docker::host(host))
+    }
+
+    fn with(docker: docker::Docker) -> Result<Docker> {
+        let runtime = Builder::new_current_thread().enable_all().build()?;
+
+        Ok(Docker { docker, runtime })
+    }
+
+    /// Exports an interface for interacting with docker images
+    pub fn images(&self) -> Images<'_> {
+        Images {
+            images: self.docker.images(),
+            runtime: &self.runtime,
+        }
+    }
+
+    /// Exports an interface for interacting with docker containers
+    pub fn containers(&self) -> Containers<'_> {
+        Containers {
+            containers: self.docker.containers(),
+            runtime: &self.runtime,
+        }
+    }
+
+    /// Exports an interface for interacting with docker networks
+    pub fn networks(&self) -> Networks<'_> {
+        Networks {
+            networks: self.docker.networks(),
+            runtime: &self.runtime,
+        }
+    }
+
+    /// Exports an interface for interacting with docker volumes
+    pub fn volumes(&self) -> Volumes<'_> {
+        Volumes {
+            volumes: self.docker.volumes(),
+            runtime: &self.runtime,
+        }
+    }
+
+    /// Returns version information associated with the docker daemon
+    pub fn version(&self) -> Result<Version> {
+        self.runtime.block_on(self.docker.version())
+    }
+
+    /// Returns information associated with the docker daemon
+    pub fn info(&self) -> Result<Info> {
+        self.runtime.block_on(self.docker.info())
+    }
+
+    /// Returns the disk space used by images, containers and volumes
+    pub fn data_usage(&self) -> Result<DataUsage> {
+        self.runtime.block_on(self.docker.data_usage())
+    }
+
+    /// Returns which images, containers and volumes are unused and how much disk space removing
+    /// them would free
+    pub fn reclaimable(&self) -> Result<ReclaimableReport> {
+        self.runtime.block_on(self.docker.reclaimable())
+    }
+
+    /// Validates credentials for a registry, returning an identity token when the registry
+    /// supports them
+    pub fn auth(
+        &self,
+        auth: &RegistryAuth,
+    ) -> Result<AuthInfo> {
+        self.runtime.block_on(self.docker.auth(auth))
+    }
+
+    /// Validates credentials for a registry like `docker login`, returning the identity token
+    /// issued by the registry if it supports them, otherwise the given credentials
+    pub fn login(
+        &self,
+        auth: &RegistryAuth,
+    ) -> Result<RegistryAuth> {
+        self.runtime.block_on(self.docker.login(auth))
+    }
+
+    /// Returns a simple ping response indicating the docker daemon is accessible
+    pub fn ping(&self) -> Result<String> {
+        self.runtime.block_on(self.docker.ping())
+    }
+
+    /// Returns an iterator of docker events
+    pub fn events(
+        &self,
+        opts: &EventsOptions,
+    ) -> Iter<'_, Event> {
+        Iter::new(&self.runtime, self.docker.events(opts))
+    }
+}
+
+/// An `Iterator` which blocks on the next item of a stream
+pub struct Iter<'docker, T> {
+    runtime: &'docker Runtime,
+    stream: Pin<Box<dyn Stream<Item = Result<T>> + 'docker>>,
+}
+
+impl<'docker, T> Iter<'docker, T> {
+    fn new<S>(
+        runtime: &'docker Runtime,
+        stream: S,
+    ) -> Self
+    where
+        S: Stream<Item = Result<T>> + 'docker,
+    {
+        Iter {
+            runtime,
+            stream: Box::pin(stream),
+        }
+    }
+}
+
+impl<'docker, T> Iterator for Iter<'docker, T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.runtime.block_on(self.stream.next())
+    }
+}
+
+/// Blocking counterpart of [Containers](crate::container::Containers)
+pub struct Containers<'docker> {
+    containers: container::Containers<'docker>,
+    runtime: &'docker Runtime,
+}
+
+impl<'docker> Containers<'docker> {
+    /// Lists the container instances on the docker host
+    pub fn list(
+        &self,
+        opts: &ContainerListOptions,
+    ) -> Result<Vec<ContainerInfo>> {
+        self.runtime.block_on(self.containers.list(opts))
+    }
+
+    /// Returns a reference to a set of operations available to a specific container instance
+    pub fn get<S>(
+        &self,
+        name: S,
+    ) -> Container<'docker>
+    where
+        S: Into<String>,
+    {
+        Container {
+            container: self.containers.get(name),
+            runtime: self.runtime,
+        }
+    }
+
+    /// Returns a builder interface for creating a new container instance
+    pub fn create(
+        &self,
+        opts: &ContainerOptions,
+    ) -> Result<ContainerCreateInfo> {
+        self.runtime.block_on(self.containers.create(opts))
+    }
+}
+
+/// Blocking counterpart of [Container](crate::container::Container)
+pub struct Container<'docker> {
+    container: container::Container<'docker>,
+    runtime: &'docker Runtime,
+}
+
+impl<'docker> Container<'docker> {
+    /// a getter for the container id
+    pub fn id(&self) -> &str {
+        self.container.id()
+    }
+
+    /// Inspects the current docker container instance's details
+    pub fn inspect(&self) -> Result<ContainerDetails> {
+        self.runtime.block_on(self.container.inspect())
+    }
+
+    /// Returns a `top` view of information about the container process
+    pub fn top(
+        &self,
+        psargs: Option<&str>,
+    ) -> Result<Top> {
+        self.runtime.block_on(self.container.top(psargs))
+    }
+
+    /// Returns a `top` view of information about the container process
+    pub fn top_with(
+        &self,
+        opts: &TopOptions,
+    ) -> Result<Top> {
+        self.runtime.block_on(self.container.top_with(opts))
+    }
+
+    /// Returns an iterator of logs emitted but the container instance
+    pub fn logs(
+        &self,
+        opts: &LogsOptions,
+    ) -> Iter<'docker, TtyChunk> {
+        Iter::new(self.runtime, self.container.logs(opts))
+    }
+
+    /// Exports the current docker container into a tarball
+    pub fn export(&self) -> Iter<'docker, Vec<u8>> {
+        Iter::new(self.runtime, self.container.export())
+    }
+
+    /// Exports the current docker container into a tarball written to `writer`, returning its
+    /// size and SHA-256 digest
+    pub fn export_to<W>(
+        &self,
+        writer: W,
+    ) -> Result<ExportSummary>
+    where
+        W: std::io::Write,
+    {
+        use std::io::Write;
+
+        let mut writer = container::HashingWriter::new(writer);
+        for chunk in self.export() {
+            writer.write_all(&chunk?)?;
+        }
+        writer.flush()?;
+        Ok(writer.summary())
+    }
+
+    /// Exports the current docker container into a tarball written to the file at `path`,
+    /// optionally gzip compressed, returning its size and SHA-256 digest
+    pub fn export_to_file<P>(
+        &self,
+        path: P,
+        gzip: bool,
+    ) -> Result<ExportSummary>
+    where
+        P: AsRef<Path>,
+    {
+        self.runtime
+            .block_on(self.container.export_to_file(path, gzip))
+    }
+
+    /// Returns an iterator of stats specific to this container instance
+    pub fn stats(&self) -> Iter<'docker, Stats> {
+        Iter::new(self.runtime, self.container.stats())
+    }
+
+    /// Start the container instance
+    pub fn start(&self) -> Result<()> {
+        self.runtime.block_on(self.container.start())
+    }
+
+    /// Stop the container instance, returning `false` if it was already stopped
+    pub fn stop(
+        &self,
+        wait: Option<Duration>,
+    ) -> Result<bool> {
+        self.runtime.block_on(self.container.stop(wait))
+    }
+
+    /// Restart the container instance
+    pub fn restart(
+        &self,
+        wait: Option<Duration>,
+    ) -> Result<()> {
+        self.runtime.block_on(self.container.restart(wait))
+    }
+
+    /// Kill the container instance
+    pub fn kill(
+        &self,
+        signal: Option<&str>,
+    ) -> Result<()> {
+        self.runtime.block_on(self.container.kill(signal))
+    }
+
+    /// Rename the container instance
+    pub fn rename(
+        &self,
+        name: &str,
+    ) -> Result<()> {
+        self.runtime.block_on(self.container.rename(name))
+    }
+
+    /// Pause the container instance
+    pub fn pause(&self) -> Result<()> {
+        self.runtime.block_on(self.container.pause())
+    }
+
+    /// Unpause the container instance
+    pub fn unpause(&self) -> Result<()> {
+        self.runtime.block_on(self.container.unpause())
+    }
+
+    /// Wait until the container stops
+    pub fn wait(&self) -> Result<Exit> {
+        self.runtime.block_on(self.container.wait())
+    }
+
+    /// Delete the container instance
+    pub fn delete(&self) -> Result<()> {
+        self.runtime.block_on(self.container.delete())
+    }
+
+    /// Delete the container instance using the force/v options
+    pub fn remove(
+        &self,
+        opts: RmContainerOptions,
+    ) -> Result<()> {
+        self.runtime.block_on(self.container.remove(opts))
+    }
+
+    /// Copy a file/folder from the container. The resulting iterator yields a tarball of the
+    /// extracted files.
+    pub fn copy_from(
+        &self,
+        path: &Path,
+    ) -> Iter<'docker, Vec<u8>> {
+        Iter::new(self.runtime, self.container.copy_from(path))
+    }
+
+    /// Copy a byte slice as file into (see `bytes`) the container.
+    pub fn copy_file_into<P: AsRef<Path>>(
+        &self,
+        path: P,
+        bytes: &[u8],
+    ) -> Result<()> {
+        self.runtime
+            .block_on(self.container.copy_file_into(path, bytes))
+    }
+
+    /// Copy a byte slice as file into (see `bytes`) the container with the given mode and owner.
+    pub fn copy_file_into_with<P: AsRef<Path>>(
+        &self,
+        path: P,
+        bytes: &[u8],
+        mode: u32,
+        uid: u64,
+        gid: u64,
+    ) -> Result<()> {
+        self.runtime.block_on(
+            self.container
+                .copy_file_into_with(path, bytes, mode, uid, gid),
+        )
+    }
+}
+
+/// Blocking counterpart of [Images](crate::image::Images)
+pub struct Images<'docker> {
+    images: image::Images<'docker>,
+    runtime: &'docker Runtime,
+}
+
+impl<'docker> Images<'docker> {
+    /// Lists the docker images on the current docker host
+    pub fn list(
+        &self,
+        opts: &ImageListOptions,
+    ) -> Result<Vec<ImageInfo>> {
+        self.runtime.block_on(self.images.list(opts))
+    }
+
+    /// Returns a reference to a set of operations available for a named image
+    pub fn get<S>(
+        &self,
+        name: S,
+    ) -> Image<'docker>
+    where
+        S: Into<String>,
+    {
+        Image {
+            image: self.images.get(name),
+            runtime: self.runtime,
+        }
+    }
+
+    /// Search for docker images by term
+    pub fn search(
+        &self,
+        term: &str,
+    ) -> Result<Vec<SearchResult>> {
+        self.runtime.block_on(self.images.search(term))
+    }
+
+    /// Pull and create a new docker images from an existing image
+    pub fn pull(
+        &self,
+        opts: &PullOptions,
+    ) -> Iter<'docker, ImageBuildChunk> {
+        Iter::new(self.runtime, self.images.pull(opts))
+    }
+}
+
+/// Blocking counterpart of [Image](crate::image::Image)
+pub struct Image<'docker> {
+    image: image::Image<'docker>,
+    runtime: &'docker Runtime,
+}
+
+impl<'docker> Image<'docker> {
+    /// Inspects a named image's details
+    pub fn inspect(&self) -> Result<ImageDetails> {
+        self.runtime.block_on(self.image.inspect())
+    }
+
+    /// Lists the history of the images set of changes
+    pub fn history(&self) -> Result<Vec<History>> {
+        self.runtime.block_on(self.image.history())
+    }
+
+    /// Deletes an image
+    pub fn delete(&self) -> Result<Vec<Status>> {
+        self.runtime.block_on(self.image.delete())
+    }
+
+    /// Export this image to a tarball
+    pub fn export(&self) -> Iter<'docker, Vec<u8>> {
+        Iter::new(self.runtime, self.image.export())
+    }
+
+    /// Adds a tag to an image
+    pub fn tag(
+        &self,
+        opts: &TagOptions,
+    ) -> Result<()> {
+        self.runtime.block_on(self.image.tag(opts))
+    }
+}
+
+/// Blocking counterpart of [Networks](crate::network::Networks)
+pub struct Networks<'docker> {
+    networks: network::Networks<'docker>,
+    runtime: &'docker Runtime,
+}
+
+impl<'docker> Networks<'docker> {
+    /// List the docker networks on the current docker host
+    pub fn list(
+        &self,
+        opts: &NetworkListOptions,
+    ) -> Result<Vec<NetworkDetails>> {
+        self.runtime.block_on(self.networks.list(opts))
+    }
+
+    /// Returns a reference to a set of operations available to a specific network instance
+    pub fn get<S>(
+        &self,
+        id: S,
+    ) -> Network<'docker>
+    where
+        S: Into<String>,
+    {
+        Network {
+            network: self.networks.get(id),
+            runtime: self.runtime,
+        }
+    }
+
+    /// Create a new Network instance
+    pub fn create(
+        &self,
+        opts: &NetworkCreateOptions,
+    ) -> Result<NetworkCreateInfo> {
+        self.runtime.block_on(self.networks.create(opts))
+    }
+}
+
+/// Blocking counterpart of [Network](crate::network::Network)
+pub struct Network<'docker> {
+    network: network::Network<'docker>,
+    runtime: &'docker Runtime,
+}
+
+impl<'docker> Network<'docker> {
+    /// a getter for the Network id
+    pub fn id(&self) -> &str {
+        self.network.id()
+    }
+
+    /// Inspects the current docker network instance's details
+    pub fn inspect(&self) -> Result<NetworkDetails> {
+        self.runtime.block_on(self.network.inspect())
+    }
+
+    /// Delete the network instance
+    pub fn delete(&self) -> Result<()> {
+        self.runtime.block_on(self.network.delete())
+    }
+
+    /// Connect container to network
+    pub fn connect(
+        &self,
+        opts: &ContainerConnectionOptions,
+    ) -> Result<()> {
+        self.runtime.block_on(self.network.connect(opts))
+    }
+
+    /// Disconnect container to network
+    pub fn disconnect(
+        &self,
+        opts: &ContainerConnectionOptions,
+    ) -> Result<()> {
+        self.runtime.block_on(self.network.disconnect(opts))
+    }
+}
+
+/// Blocking counterpart of [Volumes](crate::volume::Volumes)
+pub struct Volumes<'docker> {
+    volumes: volume::Volumes<'docker>,
+    runtime: &'docker Runtime,
+}
+
+impl<'docker> Volumes<'docker> {
+    /// Creates a new docker volume.
+    pub fn create(
+        &self,
+        opts: &VolumeCreateOptions,
+    ) -> Result<VolumeCreateInfo> {
+        self.runtime.block_on(self.volumes.create(opts))
+    }
+
+    /// Lists the docker volumes on the current docker host
+    pub fn list(&self) -> Result<Vec<VolumeInfo>> {
+        self.runtime.block_on(self.volumes.list())
+    }
+
+    /// Returns a reference to a set of operations available for a named volume
+    pub fn get(
+        &self,
+        name: &str,
+    ) -> Volume<'docker> {
+        Volume {
+            volume: self.volumes.get(name),
+            runtime: self.runtime,
+        }
+    }
+}
+
+/// Blocking counterpart of [Volume](crate::volume::Volume)
+pub struct Volume<'docker> {
+    volume: volume::Volume<'docker>,
+    runtime: &'docker Runtime,
+}
+
+impl<'docker> Volume<'docker> {
+    /// Deletes a volume
+    pub fn delete(&self) -> Result<()> {
+        self.runtime.block_on(self.volume.delete())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "unix-socket")]
+    #[test]
+    fn unreachable_daemon_errors() {
+        let docker = super::Docker::unix("/nonexistent/docker.sock").unwrap();
+        assert!(docker.ping().is_err());
+    }
+}
@@ -2,29 +2,41 @@
 //!
 //! API Reference: <https://docs.docker.com/engine/api/v1.41/#tag/Container>
 
-use std::{collections::HashMap, hash::Hash, io, iter::Peekable, path::Path, time::Duration};
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+    iter::Peekable,
+    path::Path,
+    time::Duration,
+};
 
 use futures_util::{
     io::{AsyncRead, AsyncWrite},
     stream::Stream,
     TryStreamExt,
 };
-use hyper::Body;
+use hyper::{Body, StatusCode};
 use mime::Mime;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
 use url::form_urlencoded;
 
 use crate::{
     docker::Docker,
     errors::{Error, Result},
     exec::{Exec, ExecContainerOptions},
+    file,
     image::ContainerConfig,
-    network::NetworkSettings,
+    network::{NetworkSettings, NetworkSettingsSummary},
+    tarball,
     transport::Payload,
     tty::{self, Multiplexer as TtyMultiPlexer},
 };
 
+#[cfg(feature = "blocking")]
+use std::io::{self, Write};
+
 #[cfg(feature = "chrono")]
 use crate::datetime::datetime_from_unix_timestamp;
 #[cfg(feature = "chrono")]
@@ -67,19 +79,41 @@ impl<'docker> Container<'docker> {
             .await
     }
 
-    /// Returns a `top` view of information about the container process
+    /// Inspects the current docker container instance's details as raw JSON, giving access to
+    /// fields not modeled by [ContainerDetails](ContainerDetails)
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerInspect)
+    pub async fn inspect_raw(&self) -> Result<Value> {
+        self.docker
+            .get_json(&format!("/containers/{}/json", self.id)[..])
+            .await
+    }
+
+    /// Returns a `top` view of information about the container process, listing the processes
+    /// with the given `ps` arguments, or `-ef` if `None`.
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerTop)
     pub async fn top(
         &self,
         psargs: Option<&str>,
+    ) -> Result<Top> {
+        let mut builder = TopOptions::builder();
+        if let Some(args) = psargs {
+            builder.ps_args(args);
+        }
+        self.top_with(&builder.build()).await
+    }
+
+    /// Returns a `top` view of information about the container process
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerTop)
+    pub async fn top_with(
+        &self,
+        opts: &TopOptions,
     ) -> Result<Top> {
         let mut path = vec![format!("/containers/{}/top", self.id)];
-        if let Some(ref args) = psargs {
-            let encoded = form_urlencoded::Serializer::new(String::new())
-                .append_pair("ps_args", args)
-                .finish();
-            path.push(encoded)
+        if let Some(query) = opts.serialize()? {
+            path.push(query)
         }
         self.docker.get_json(&path.join("?")).await
     }
@@ -96,9 +130,35 @@ impl<'docker> Container<'docker> {
             path.push(query)
         }
 
-        let stream = Box::pin(self.docker.stream_get(path.join("?")));
+        decode_logs(self.docker.stream_get(path.join("?")))
+    }
 
-        Box::pin(tty::decode(stream))
+    /// Writes the logs of the container instance to a file at `path`, optionally gzip
+    /// compressed, returning the number of bytes of logs written before compression. The file is
+    /// removed again if fetching or writing the logs fails.
+    ///
+    /// When `opts` enable [timestamps](LogsOptionsBuilder::timestamps), the lines of stdout and
+    /// stderr are interleaved in timestamp order, holding a line back until the other stream has
+    /// produced a later one or the logs end. With [follow](LogsOptionsBuilder::follow) the file
+    /// is written until the container stops, so a container only logging to one of the streams
+    /// has those lines held back until then.
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerLogs)
+    pub async fn logs_to_file<P>(
+        &self,
+        opts: &LogsOptions,
+        path: P,
+        gzip: bool,
+    ) -> Result<u64>
+    where
+        P: AsRef<Path>,
+    {
+        let logs = self.logs(opts);
+        if opts.params.get("timestamps").map(String::as_str) == Some("true") {
+            file::write_stream(path.as_ref(), gzip, Box::pin(merge_lines(logs))).await
+        } else {
+            file::write_stream(path.as_ref(), gzip, logs.map_ok(|chunk| chunk.to_vec())).await
+        }
     }
 
     /// Attaches a multiplexed TCP stream to the container that can be used to read Stdout, Stderr and write Stdin.
@@ -138,6 +198,14 @@ impl<'docker> Container<'docker> {
             .await
     }
 
+    /// Returns a set of changes made to the container instance, the same as
+    /// [changes](Container::changes)
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerChanges)
+    pub async fn diff(&self) -> Result<Vec<Change>> {
+        self.changes().await
+    }
+
     /// Exports the current docker container into a tarball
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerExport)
@@ -147,25 +215,35 @@ impl<'docker> Container<'docker> {
             .map_ok(|c| c.to_vec())
     }
 
+    /// Exports the current docker container into a tarball written to the file at `path`,
+    /// gzip-compressing it on the fly if `gzip` is set, and returns the size and SHA-256 digest
+    /// of the tarball computed while streaming. The file is removed again if the export fails.
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerExport)
+    pub async fn export_to_file<P>(
+        &self,
+        path: P,
+        gzip: bool,
+    ) -> Result<ExportSummary>
+    where
+        P: AsRef<Path>,
+    {
+        let mut hasher = Sha256::new();
+        let export = self.export().inspect_ok(|chunk| hasher.update(chunk));
+        let bytes = file::write_stream(path.as_ref(), gzip, Box::pin(export)).await?;
+        Ok(ExportSummary {
+            bytes,
+            sha256: format!("{:x}", hasher.finalize()),
+        })
+    }
+
     /// Returns a stream of stats specific to this container instance
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerStats)
     pub fn stats(&self) -> impl Stream<Item = Result<Stats>> + Unpin + 'docker {
-        let codec = futures_codec::LinesCodec {};
-
-        let reader = Box::pin(
-            self.docker
-                .stream_get(format!("/containers/{}/stats", self.id))
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e)),
-        )
-        .into_async_read();
-
         Box::pin(
-            futures_codec::FramedRead::new(reader, codec)
-                .map_err(Error::IO)
-                .and_then(|s: String| async move {
-                    serde_json::from_str(&s).map_err(Error::SerdeJsonError)
-                }),
+            self.docker
+                .stream_get_into(format!("/containers/{}/stats", self.id)),
         )
     }
 
@@ -181,11 +259,14 @@ impl<'docker> Container<'docker> {
 
     /// Stop the container instance
     ///
+    /// Returns `false` if the container was already stopped and fails with
+    /// [Error::NotFound](Error::NotFound) if it doesn't exist.
+    ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerStop)
     pub async fn stop(
         &self,
         wait: Option<Duration>,
-    ) -> Result<()> {
+    ) -> Result<bool> {
         let mut path = vec![format!("/containers/{}/stop", self.id)];
         if let Some(w) = wait {
             let encoded = form_urlencoded::Serializer::new(String::new())
@@ -194,11 +275,11 @@ impl<'docker> Container<'docker> {
 
             path.push(encoded)
         }
-        self.docker.post(&path.join("?"), None).await?;
-        Ok(())
+        changed(not_found(self.docker.post(&path.join("?"), None).await))
     }
 
-    /// Restart the container instance
+    /// Restart the container instance, failing with [Error::NotFound](Error::NotFound) if it
+    /// doesn't exist
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerRestart)
     pub async fn restart(
@@ -212,11 +293,12 @@ impl<'docker> Container<'docker> {
                 .finish();
             path.push(encoded)
         }
-        self.docker.post(&path.join("?"), None).await?;
+        not_found(self.docker.post(&path.join("?"), None).await)?;
         Ok(())
     }
 
-    /// Kill the container instance
+    /// Kill the container instance, failing with [Error::NotFound](Error::NotFound) if it
+    /// doesn't exist
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerKill)
     pub async fn kill(
@@ -230,19 +312,22 @@ impl<'docker> Container<'docker> {
                 .finish();
             path.push(encoded)
         }
-        self.docker.post(&path.join("?"), None).await?;
+        not_found(self.docker.post(&path.join("?"), None).await)?;
         Ok(())
     }
 
     /// Rename the container instance
     ///
+    /// `name` may be given with or without the leading `/` the daemon reports names with, see
+    /// [normalize_name](normalize_name).
+    ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerRename)
     pub async fn rename(
         &self,
         name: &str,
     ) -> Result<()> {
         let query = form_urlencoded::Serializer::new(String::new())
-            .append_pair("name", name)
+            .append_pair("name", normalize_name(name))
             .finish();
         self.docker
             .post(
@@ -253,24 +338,74 @@ impl<'docker> Container<'docker> {
         Ok(())
     }
 
+    /// Update the resource limits and restart policy of the container instance without
+    /// recreating it
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerUpdate)
+    pub async fn update(
+        &self,
+        opts: &ContainerUpdateOptions,
+    ) -> Result<ContainerUpdateInfo> {
+        let body: Body = opts.serialize()?.into();
+        self.docker
+            .post_json(
+                format!("/containers/{}/update", self.id),
+                Some((body, mime::APPLICATION_JSON)),
+            )
+            .await
+    }
+
+    /// Create a new image from the container's changes, returning the id of the image
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ImageCommit)
+    pub async fn commit(
+        &self,
+        opts: &CommitOptions,
+    ) -> Result<String> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "PascalCase")]
+        struct Response {
+            id: String,
+        }
+
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("container", &self.id)
+            .finish();
+        let mut path = vec![format!("/commit?{}", query)];
+        if let Some(query) = opts.serialize() {
+            path.push(query)
+        }
+
+        self.docker
+            .post_json(path.join("&"), Payload::None)
+            .await
+            .map(|resp: Response| resp.id)
+    }
+
     /// Pause the container instance
     ///
+    /// Pausing an already paused container succeeds.
+    ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerPause)
     pub async fn pause(&self) -> Result<()> {
-        self.docker
+        let response = self
+            .docker
             .post(&format!("/containers/{}/pause", self.id)[..], None)
-            .await?;
-        Ok(())
+            .await;
+        ignore_fault(response, "is already paused")
     }
 
     /// Unpause the container instance
     ///
+    /// Unpausing a container which is not paused succeeds.
+    ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerUnpause)
     pub async fn unpause(&self) -> Result<()> {
-        self.docker
+        let response = self
+            .docker
             .post(&format!("/containers/{}/unpause", self.id)[..], None)
-            .await?;
-        Ok(())
+            .await;
+        ignore_fault(response, "is not paused")
     }
 
     /// Wait until the container stops
@@ -319,6 +454,19 @@ impl<'docker> Container<'docker> {
         Exec::create_and_start(self.docker, &self.id, opts)
     }
 
+    /// Create a new exec instance in this container without starting it
+    ///
+    /// Unlike [exec](Container::exec), this returns the [Exec](crate::exec::Exec) handle so that
+    /// it can be started and, for interactive sessions, resized separately.
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerExec)
+    pub async fn exec_create(
+        &self,
+        opts: &ExecContainerOptions,
+    ) -> Result<Exec<'docker>> {
+        Exec::create(self.docker, &self.id, opts).await
+    }
+
     /// Copy a file/folder from the container.  The resulting stream is a tarball of the extracted
     /// files.
     ///
@@ -326,7 +474,8 @@ impl<'docker> Container<'docker> {
     /// resource specified by `path` must exist. To assert that the resource is expected to be a
     /// directory, `path` should end in `/` or `/`. (assuming a path separator of `/`). If `path`
     /// ends in `/.`  then this indicates that only the contents of the path directory should be
-    /// copied.  A symlink is always resolved to its target.
+    /// copied.  A symlink is always resolved to its target. If `path` does not exist, the stream
+    /// yields [Error::NotFound](Error::NotFound) on its first poll.
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerArchive)
     pub fn copy_from(
@@ -338,13 +487,18 @@ impl<'docker> Container<'docker> {
             .finish();
 
         let endpoint = format!("/containers/{}/archive?{}", self.id, path_arg);
-        self.docker.stream_get(endpoint).map_ok(|c| c.to_vec())
+        self.docker
+            .stream_get(endpoint)
+            .map_ok(|c| c.to_vec())
+            .map_err(not_found_error)
     }
 
     /// Copy a byte slice as file into (see `bytes`) the container.
     ///
     /// The file will be copied at the given location (see `path`) and will be owned by root
-    /// with access mask 644.
+    /// with access mask 644. This is only meant for single files, use
+    /// [copy_dir_into](Container::copy_dir_into) to upload a whole directory and
+    /// [copy_file_into_with](Container::copy_file_into_with) to choose the owner and mode.
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/PutContainerArchive)
     pub async fn copy_file_into<P: AsRef<Path>>(
@@ -352,26 +506,46 @@ impl<'docker> Container<'docker> {
         path: P,
         bytes: &[u8],
     ) -> Result<()> {
-        let path = path.as_ref();
-
-        let mut ar = tar::Builder::new(Vec::new());
-        let mut header = tar::Header::new_gnu();
-        header.set_size(bytes.len() as u64);
-        header.set_mode(0o0644);
-        ar.append_data(
-            &mut header,
-            path.to_path_buf()
-                .iter()
-                .skip(1)
-                .collect::<std::path::PathBuf>(),
-            bytes,
-        )?;
-        let data = ar.into_inner()?;
+        self.copy_file_into_with(path, bytes, 0o0644, 0, 0).await
+    }
+
+    /// Copy a byte slice as file into (see `bytes`) the container, with the given access mask
+    /// (see `mode`) and owned by the given user and group ids (see `uid` and `gid`).
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/PutContainerArchive)
+    pub async fn copy_file_into_with<P: AsRef<Path>>(
+        &self,
+        path: P,
+        bytes: &[u8],
+        mode: u32,
+        uid: u64,
+        gid: u64,
+    ) -> Result<()> {
+        let data = file_archive(path.as_ref(), bytes, mode, uid, gid)?;
 
         self.copy_to(Path::new("/"), data.into()).await?;
         Ok(())
     }
 
+    /// Copy a local directory (see `src`) into the container.
+    ///
+    /// The contents of `src` are tarred and extracted inside the container at the given location
+    /// (see `dest`), which must already exist. The directory is tarred while it is being
+    /// uploaded, so that it never has to fit in memory.
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/PutContainerArchive)
+    pub async fn copy_dir_into(
+        &self,
+        src: &Path,
+        dest: &Path,
+    ) -> Result<()> {
+        std::fs::metadata(src)?;
+        let body = tarball::dir_body(src.to_string_lossy().into_owned(), true);
+
+        self.copy_to(dest, body).await?;
+        Ok(())
+    }
+
     /// Copy a tarball (see `body`) to the container.
     ///
     /// The tarball will be copied to the container and extracted at the given location (see `path`).
@@ -382,9 +556,27 @@ impl<'docker> Container<'docker> {
         path: &Path,
         body: Body,
     ) -> Result<()> {
-        let path_arg = form_urlencoded::Serializer::new(String::new())
+        self.copy_to_with(path, body, &CopyToOptions::default())
+            .await
+    }
+
+    /// Copy a tarball (see `body`) to the container like [copy_to](Container::copy_to),
+    /// controlling how it is extracted with `opts`.
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/PutContainerArchive)
+    pub async fn copy_to_with(
+        &self,
+        path: &Path,
+        body: Body,
+        opts: &CopyToOptions,
+    ) -> Result<()> {
+        let mut path_arg = form_urlencoded::Serializer::new(String::new())
             .append_pair("path", &path.to_string_lossy())
             .finish();
+        if let Some(query) = opts.serialize() {
+            path_arg.push('&');
+            path_arg.push_str(&query);
+        }
 
         let mime = "application/x-tar".parse::<Mime>().unwrap();
 
@@ -398,6 +590,189 @@ impl<'docker> Container<'docker> {
     }
 }
 
+/// Builds a tarball holding a single file, to be extracted at the root of a container.
+fn file_archive(
+    path: &Path,
+    bytes: &[u8],
+    mode: u32,
+    uid: u64,
+    gid: u64,
+) -> Result<Vec<u8>> {
+    let mut ar = tar::Builder::new(Vec::new());
+    let mut header = tar::Header::new_gnu();
+    header.set_size(bytes.len() as u64);
+    header.set_mode(mode);
+    header.set_uid(uid);
+    header.set_gid(gid);
+    ar.append_data(
+        &mut header,
+        path.iter().skip(1).collect::<std::path::PathBuf>(),
+        bytes,
+    )?;
+    Ok(ar.into_inner()?)
+}
+
+/// Maps the response of a state transition to whether the container's state changed, the daemon
+/// answering with `304 Not Modified` when the container already was in the requested state.
+fn changed(response: Result<String>) -> Result<bool> {
+    match response {
+        Ok(_) => Ok(true),
+        Err(Error::Fault {
+            code: StatusCode::NOT_MODIFIED,
+            ..
+        }) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Reassembles the lines of `logs`, which may be split across chunks, interleaving those of
+/// stdout and stderr by their timestamp prefix
+fn merge_lines<S>(logs: S) -> impl Stream<Item = Result<Vec<u8>>>
+where
+    S: Stream<Item = Result<tty::TtyChunk>> + Unpin,
+{
+    futures_util::stream::unfold(
+        (logs, LineMerge::default(), false),
+        |(mut logs, mut merge, mut ended)| async move {
+            loop {
+                if let Some(line) = merge.pop(ended) {
+                    return Some((Ok(line), (logs, merge, ended)));
+                }
+                if ended {
+                    return None;
+                }
+                match logs.try_next().await {
+                    Ok(Some(chunk)) => merge.push(chunk),
+                    Ok(None) => {
+                        merge.end_partial_lines();
+                        ended = true;
+                    }
+                    Err(e) => return Some((Err(e), (logs, merge, true))),
+                }
+            }
+        },
+    )
+}
+
+/// Two-way merge of the complete lines of stdout and stderr
+#[derive(Default)]
+struct LineMerge {
+    /// Incomplete last lines of stdout and stderr
+    partial: [Vec<u8>; 2],
+    /// Complete lines of stdout and stderr, each in timestamp order
+    lines: [VecDeque<Vec<u8>>; 2],
+}
+
+impl LineMerge {
+    fn push(
+        &mut self,
+        chunk: tty::TtyChunk,
+    ) {
+        let index = match chunk {
+            tty::TtyChunk::StdErr(_) => 1,
+            _ => 0,
+        };
+        let partial = &mut self.partial[index];
+        partial.extend_from_slice(&chunk);
+        while let Some(end) = partial.iter().position(|b| *b == b'\n') {
+            self.lines[index].push_back(partial.drain(..=end).collect());
+        }
+    }
+
+    /// Treats the incomplete last lines as complete once the logs have ended
+    fn end_partial_lines(&mut self) {
+        for (partial, lines) in self.partial.iter_mut().zip(self.lines.iter_mut()) {
+            if !partial.is_empty() {
+                lines.push_back(std::mem::take(partial));
+            }
+        }
+    }
+
+    /// The earliest line, as soon as it's known to precede the lines of the other stream, i.e.
+    /// both have a line waiting or the logs have `ended`
+    fn pop(
+        &mut self,
+        ended: bool,
+    ) -> Option<Vec<u8>> {
+        let [stdout, stderr] = &mut self.lines;
+        let lines = match (stdout.front(), stderr.front()) {
+            (Some(out), Some(err)) if timestamp_key(err) < timestamp_key(out) => stderr,
+            (Some(_), Some(_)) => stdout,
+            (Some(_), None) if ended => stdout,
+            (None, Some(_)) if ended => stderr,
+            _ => return None,
+        };
+        lines.pop_front()
+    }
+}
+
+/// Makes the RFC 3339 timestamp prefixing a log line sortable, the daemon trimming trailing zeros
+/// from its fractional seconds
+fn timestamp_key(line: &[u8]) -> String {
+    let timestamp = line.split(|b| *b == b' ').next().unwrap_or_default();
+    let timestamp = String::from_utf8_lossy(timestamp);
+    let (seconds, fraction) = match timestamp.split_once('.') {
+        Some((seconds, fraction)) => (seconds, fraction),
+        None => match timestamp.find(['Z', '+']) {
+            Some(index) => timestamp.split_at(index),
+            None => (&timestamp[..], ""),
+        },
+    };
+    let digits = fraction
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(fraction.len());
+    format!(
+        "{}.{:0<9}{}",
+        seconds,
+        &fraction[..digits],
+        &fraction[digits..]
+    )
+}
+
+/// Strips the leading `/` the daemon prefixes container names with, e.g. in
+/// [ContainerInfo::names](ContainerInfo::names) and [ContainerDetails::name](ContainerDetails::name),
+/// so they can be compared with the names containers were created or renamed with.
+pub fn normalize_name(name: &str) -> &str {
+    name.strip_prefix('/').unwrap_or(name)
+}
+
+/// Decodes the multiplexed log stream, reporting an unknown container as
+/// [Error::NotFound](Error::NotFound)
+fn decode_logs<'a>(
+    stream: impl Stream<Item = Result<hyper::body::Bytes>> + 'a
+) -> impl Stream<Item = Result<tty::TtyChunk>> + Unpin + 'a {
+    Box::pin(tty::decode(Box::pin(stream.map_err(not_found_error))))
+}
+
+/// Maps a `404` fault to [Error::NotFound](Error::NotFound)
+fn not_found(response: Result<String>) -> Result<String> {
+    response.map_err(not_found_error)
+}
+
+fn not_found_error(err: Error) -> Error {
+    match err {
+        Error::Fault {
+            code: StatusCode::NOT_FOUND,
+            message,
+            ..
+        } => Error::NotFound(message),
+        err => err,
+    }
+}
+
+/// Treats a fault whose message contains `reason` as success, the daemon reporting an error
+/// instead of `304 Not Modified` for some state transitions which were already applied.
+fn ignore_fault(
+    response: Result<String>,
+    reason: &str,
+) -> Result<()> {
+    match response {
+        Ok(_) => Ok(()),
+        Err(Error::Fault { ref message, .. }) if message.contains(reason) => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
 /// Interface for docker containers
 ///
 /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#tag/Containers)
@@ -427,6 +802,21 @@ impl<'docker> Containers<'docker> {
             .await
     }
 
+    /// Lists all container instances, including non-running ones, created after the container
+    /// with the given id or name. The daemon returns an error if no such container exists.
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ContainerList)
+    pub async fn list_all_since(
+        &self,
+        id: &str,
+    ) -> Result<Vec<ContainerInfo>> {
+        let opts = ContainerListOptions::builder()
+            .all()
+            .filter(vec![ContainerFilter::Since(id.to_owned())])
+            .build();
+        self.list(&opts).await
+    }
+
     /// Returns a reference to a set of operations available to a specific container instance
     pub fn get<S>(
         &self,
@@ -458,6 +848,18 @@ impl<'docker> Containers<'docker> {
             .post_json(&path.join("?"), Some((body, mime::APPLICATION_JSON)))
             .await
     }
+
+    /// Creates a new container instance and starts it, returning a reference to the started
+    /// container
+    pub async fn run(
+        &self,
+        opts: &ContainerOptions,
+    ) -> Result<Container<'docker>> {
+        let info = self.create(opts).await?;
+        let container = self.get(info.id);
+        container.start().await?;
+        Ok(container)
+    }
 }
 
 /// Options for filtering container list results
@@ -493,6 +895,21 @@ pub enum ContainerFilter {
     LabelName(String),
     Label(String, String),
     Name(String),
+    /// One of `starting`, `healthy`, `unhealthy` or `none`
+    Health(String),
+    IsTask(bool),
+    /// A published port, in the form `<port>[/<proto>]` or `<startport-endport>/[<proto>]`
+    Publish(String),
+    /// An exposed port, in the form `<port>[/<proto>]` or `<startport-endport>/[<proto>]`
+    Expose(String),
+    /// A volume name or mount point destination
+    Volume(String),
+    /// A network id or name
+    Network(String),
+    /// Containers created after the container with the given id or name
+    Since(String),
+    /// Containers created before the container with the given id or name
+    Before(String),
 }
 
 /// Builder interface for `ContainerListOptions`
@@ -514,6 +931,14 @@ impl ContainerListOptionsBuilder {
                 ContainerFilter::LabelName(n) => ("label", n),
                 ContainerFilter::Label(n, v) => ("label", format!("{}={}", n, v)),
                 ContainerFilter::Name(n) => ("name", n.to_string()),
+                ContainerFilter::Health(h) => ("health", h),
+                ContainerFilter::IsTask(t) => ("is-task", t.to_string()),
+                ContainerFilter::Publish(p) => ("publish", p),
+                ContainerFilter::Expose(e) => ("expose", e),
+                ContainerFilter::Volume(v) => ("volume", v),
+                ContainerFilter::Network(n) => ("network", n),
+                ContainerFilter::Since(id) => ("since", id),
+                ContainerFilter::Before(id) => ("before", id),
             };
 
             param.entry(key).or_insert_with(Vec::new).push(value);
@@ -530,6 +955,10 @@ impl ContainerListOptionsBuilder {
         self
     }
 
+    /// Only show containers created after the container with the given id.
+    ///
+    /// This is a legacy parameter which recent daemons ignore, prefer
+    /// [ContainerFilter::Since](ContainerFilter::Since).
     pub fn since(
         &mut self,
         since: &str,
@@ -538,6 +967,10 @@ impl ContainerListOptionsBuilder {
         self
     }
 
+    /// Only show containers created before the container with the given id.
+    ///
+    /// This is a legacy parameter which recent daemons ignore, prefer
+    /// [ContainerFilter::Before](ContainerFilter::Before).
     pub fn before(
         &mut self,
         before: &str,
@@ -551,6 +984,16 @@ impl ContainerListOptionsBuilder {
         self
     }
 
+    /// Return this number of most recently created containers, including non-running ones.
+    /// A negative value returns all containers.
+    pub fn limit(
+        &mut self,
+        n: i64,
+    ) -> &mut Self {
+        self.params.insert("limit", n.to_string());
+        self
+    }
+
     pub fn build(&self) -> ContainerListOptions {
         ContainerListOptions {
             params: self.params.clone(),
@@ -779,6 +1222,36 @@ impl ContainerOptionsBuilder {
         self
     }
 
+    /// Memory soft limit in bytes.
+    pub fn memory_reservation(
+        &mut self,
+        memory_reservation: i64,
+    ) -> &mut Self {
+        self.params
+            .insert("HostConfig.MemoryReservation", json!(memory_reservation));
+        self
+    }
+
+    /// Tune the container's memory swappiness behavior. Accepts an integer between 0 and 100.
+    pub fn memory_swappiness(
+        &mut self,
+        memory_swappiness: i64,
+    ) -> &mut Self {
+        self.params
+            .insert("HostConfig.MemorySwappiness", json!(memory_swappiness));
+        self
+    }
+
+    /// Disable the OOM killer for the container.
+    pub fn oom_kill_disable(
+        &mut self,
+        disable: bool,
+    ) -> &mut Self {
+        self.params
+            .insert("HostConfig.OomKillDisable", json!(disable));
+        self
+    }
+
     /// CPU quota in units of 10<sup>-9</sup> CPUs. Set to 0 (default) for there to be no limit.
     ///
     /// For example, setting `nano_cpus` to `500_000_000` results in the container being allocated
@@ -812,6 +1285,76 @@ impl ContainerOptionsBuilder {
         self
     }
 
+    /// The length of a CPU real-time period in microseconds. Requires a kernel with real-time
+    /// group scheduling enabled.
+    pub fn cpu_realtime_period(
+        &mut self,
+        period: i64,
+    ) -> &mut Self {
+        self.params
+            .insert("HostConfig.CpuRealtimePeriod", json!(period));
+        self
+    }
+
+    /// The length of a CPU real-time runtime in microseconds.
+    pub fn cpu_realtime_runtime(
+        &mut self,
+        runtime: i64,
+    ) -> &mut Self {
+        self.params
+            .insert("HostConfig.CpuRealtimeRuntime", json!(runtime));
+        self
+    }
+
+    /// Block IO weight (relative weight), accepts a weight value between 10 and 1000.
+    pub fn blkio_weight(
+        &mut self,
+        weight: u16,
+    ) -> &mut Self {
+        self.params.insert("HostConfig.BlkioWeight", json!(weight));
+        self
+    }
+
+    /// Limit read rate (bytes per second) from a device.
+    pub fn blkio_device_read_bps(
+        &mut self,
+        devices: Vec<ThrottleDevice>,
+    ) -> &mut Self {
+        self.params
+            .insert("HostConfig.BlkioDeviceReadBps", json!(devices));
+        self
+    }
+
+    /// Limit write rate (bytes per second) to a device.
+    pub fn blkio_device_write_bps(
+        &mut self,
+        devices: Vec<ThrottleDevice>,
+    ) -> &mut Self {
+        self.params
+            .insert("HostConfig.BlkioDeviceWriteBps", json!(devices));
+        self
+    }
+
+    /// Limit read rate (IO per second) from a device.
+    pub fn blkio_device_read_iops(
+        &mut self,
+        devices: Vec<ThrottleDevice>,
+    ) -> &mut Self {
+        self.params
+            .insert("HostConfig.BlkioDeviceReadIOps", json!(devices));
+        self
+    }
+
+    /// Limit write rate (IO per second) to a device.
+    pub fn blkio_device_write_iops(
+        &mut self,
+        devices: Vec<ThrottleDevice>,
+    ) -> &mut Self {
+        self.params
+            .insert("HostConfig.BlkioDeviceWriteIOps", json!(devices));
+        self
+    }
+
     pub fn labels(
         &mut self,
         labels: &HashMap<&str, &str>,
@@ -881,6 +1424,22 @@ impl ContainerOptionsBuilder {
         self
     }
 
+    /// Aliases of the container on a user-defined `network` it is connected to at creation,
+    /// under which other containers on the network can discover it
+    pub fn network_aliases(
+        &mut self,
+        network: &str,
+        aliases: Vec<&str>,
+    ) -> &mut Self {
+        // network names may contain dots, so the endpoints are kept as a single object
+        let endpoints = self
+            .params
+            .entry("NetworkingConfig.EndpointsConfig")
+            .or_insert_with(|| json!({}));
+        endpoints[network]["Aliases"] = json!(aliases);
+        self
+    }
+
     pub fn env<E, S>(
         &mut self,
         envs: E,
@@ -985,6 +1544,13 @@ impl ContainerOptionsBuilder {
         self
     }
 
+    /// Disable any healthcheck inherited from the image (corresponds to the `--no-healthcheck`
+    /// docker cli argument)
+    pub fn disable_healthcheck(&mut self) -> &mut Self {
+        self.params.insert("Healthcheck.Test", json!(["NONE"]));
+        self
+    }
+
     pub fn userns_mode(
         &mut self,
         mode: &str,
@@ -1001,6 +1567,25 @@ impl ContainerOptionsBuilder {
         self
     }
 
+    /// Runtime to use for this container, e.g. `runc`, `runsc` or `nvidia`.
+    pub fn runtime(
+        &mut self,
+        runtime: &str,
+    ) -> &mut Self {
+        self.params.insert("HostConfig.Runtime", json!(runtime));
+        self
+    }
+
+    /// Storage driver options for this container, e.g. `size=10G` to limit the size of its
+    /// writable layer.
+    pub fn storage_opt(
+        &mut self,
+        opts: &HashMap<&str, &str>,
+    ) -> &mut Self {
+        self.params.insert("HostConfig.StorageOpt", json!(opts));
+        self
+    }
+
     pub fn user(
         &mut self,
         user: &str,
@@ -1017,6 +1602,245 @@ impl ContainerOptionsBuilder {
     }
 }
 
+/// Interface for updating the resources and restart policy of an existing container
+#[derive(Serialize, Debug)]
+pub struct ContainerUpdateOptions {
+    params: HashMap<&'static str, Value>,
+}
+
+impl ContainerUpdateOptions {
+    /// return a new instance of a builder for options
+    pub fn builder() -> ContainerUpdateOptionsBuilder {
+        ContainerUpdateOptionsBuilder::default()
+    }
+
+    /// serialize options as a string. returns None if no options are defined
+    pub fn serialize(&self) -> Result<String> {
+        let mut body = Value::Object(Map::new());
+        for (k, v) in &self.params {
+            insert(&mut k.split('.').peekable(), v, &mut body)
+        }
+        serde_json::to_string(&body).map_err(Error::from)
+    }
+}
+
+#[derive(Default)]
+pub struct ContainerUpdateOptionsBuilder {
+    params: HashMap<&'static str, Value>,
+}
+
+impl ContainerUpdateOptionsBuilder {
+    /// Memory limit in bytes.
+    pub fn memory(
+        &mut self,
+        memory: u64,
+    ) -> &mut Self {
+        self.params.insert("Memory", json!(memory));
+        self
+    }
+
+    /// Total memory limit (memory + swap) in bytes. Set to -1 to enable unlimited swap.
+    pub fn memory_swap(
+        &mut self,
+        memory_swap: i64,
+    ) -> &mut Self {
+        self.params.insert("MemorySwap", json!(memory_swap));
+        self
+    }
+
+    /// Memory soft limit in bytes.
+    pub fn memory_reservation(
+        &mut self,
+        memory_reservation: i64,
+    ) -> &mut Self {
+        self.params
+            .insert("MemoryReservation", json!(memory_reservation));
+        self
+    }
+
+    /// CPU quota in units of 10<sup>-9</sup> CPUs.
+    pub fn nano_cpus(
+        &mut self,
+        nano_cpus: u64,
+    ) -> &mut Self {
+        self.params.insert("NanoCpus", json!(nano_cpus));
+        self
+    }
+
+    /// CPU quota in units of CPUs. This is a wrapper around `nano_cpus` to do the unit conversion.
+    pub fn cpus(
+        &mut self,
+        cpus: f64,
+    ) -> &mut Self {
+        self.nano_cpus((1_000_000_000.0 * cpus) as u64)
+    }
+
+    /// Sets an integer value representing the container's relative CPU weight versus other
+    /// containers.
+    pub fn cpu_shares(
+        &mut self,
+        cpu_shares: u32,
+    ) -> &mut Self {
+        self.params.insert("CpuShares", json!(cpu_shares));
+        self
+    }
+
+    /// Block IO weight (relative weight), accepts a weight value between 10 and 1000.
+    pub fn blkio_weight(
+        &mut self,
+        weight: u16,
+    ) -> &mut Self {
+        self.params.insert("BlkioWeight", json!(weight));
+        self
+    }
+
+    /// Memory nodes (MEMs) in which to allow execution, e.g. `0-3` or `0,1`. Only effective on
+    /// NUMA systems.
+    pub fn cpuset_mems(
+        &mut self,
+        mems: &str,
+    ) -> &mut Self {
+        self.params.insert("CpusetMems", json!(mems));
+        self
+    }
+
+    /// Change the restart policy of the container, see
+    /// [ContainerOptionsBuilder::restart_policy](ContainerOptionsBuilder::restart_policy).
+    pub fn restart_policy(
+        &mut self,
+        name: &str,
+        maximum_retry_count: u64,
+    ) -> &mut Self {
+        self.params.insert("RestartPolicy.Name", json!(name));
+        if name == "on-failure" {
+            self.params.insert(
+                "RestartPolicy.MaximumRetryCount",
+                json!(maximum_retry_count),
+            );
+        }
+        self
+    }
+
+    pub fn build(&self) -> ContainerUpdateOptions {
+        ContainerUpdateOptions {
+            params: self.params.clone(),
+        }
+    }
+}
+
+/// The size and digest of a tarball written by
+/// [Container::export_to_file](Container::export_to_file)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportSummary {
+    pub bytes: u64,
+    /// Hex encoded SHA-256 digest of the tarball
+    pub sha256: String,
+}
+
+/// Counts and hashes the bytes written through it
+#[cfg(feature = "blocking")]
+pub(crate) struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    bytes: u64,
+}
+
+#[cfg(feature = "blocking")]
+impl<W: Write> HashingWriter<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        HashingWriter {
+            inner,
+            hasher: Sha256::new(),
+            bytes: 0,
+        }
+    }
+
+    pub(crate) fn summary(self) -> ExportSummary {
+        ExportSummary {
+            bytes: self.bytes,
+            sha256: format!("{:x}", self.hasher.finalize()),
+        }
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Options for listing the processes of a container with [Container::top_with](Container::top_with)
+#[derive(Default, Debug)]
+pub struct TopOptions {
+    params: HashMap<&'static str, String>,
+}
+
+impl TopOptions {
+    /// return a new instance of a builder for options
+    pub fn builder() -> TopOptionsBuilder {
+        TopOptionsBuilder::default()
+    }
+
+    /// serialize options as a string. returns None if no options are defined, in which case the
+    /// daemon uses `-ef`. Fails on `ps` arguments that are empty or contain control characters.
+    pub fn serialize(&self) -> Result<Option<String>> {
+        if let Some(args) = self.params.get("ps_args") {
+            if args.trim().is_empty() {
+                return Err(Error::InvalidArgument("ps_args must not be empty".into()));
+            }
+            if args.chars().any(char::is_control) {
+                return Err(Error::InvalidArgument(format!(
+                    "ps_args must not contain control characters: {:?}",
+                    args
+                )));
+            }
+        }
+        if self.params.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(
+                form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(&self.params)
+                    .finish(),
+            ))
+        }
+    }
+}
+
+/// Builder interface for `TopOptions`
+#[derive(Default)]
+pub struct TopOptionsBuilder {
+    params: HashMap<&'static str, String>,
+}
+
+impl TopOptionsBuilder {
+    /// Arguments passed to `ps`, e.g. `aux`. Defaults to `-ef` on Linux.
+    pub fn ps_args(
+        &mut self,
+        args: &str,
+    ) -> &mut Self {
+        self.params.insert("ps_args", args.to_owned());
+        self
+    }
+
+    pub fn build(&self) -> TopOptions {
+        TopOptions {
+            params: self.params.clone(),
+        }
+    }
+}
+
 /// Options for controlling log request results
 #[derive(Default, Debug)]
 pub struct LogsOptions {
@@ -1083,6 +1907,8 @@ impl LogsOptionsBuilder {
     }
 
     /// how_many can either be "all" or a to_string() of the number
+    ///
+    /// Prefer [tail_lines](LogsOptionsBuilder::tail_lines) to only return a number of lines.
     pub fn tail(
         &mut self,
         how_many: &str,
@@ -1091,6 +1917,15 @@ impl LogsOptionsBuilder {
         self
     }
 
+    /// Only return this number of lines from the end of the logs
+    pub fn tail_lines(
+        &mut self,
+        n: u64,
+    ) -> &mut Self {
+        self.params.insert("tail", n.to_string());
+        self
+    }
+
     #[cfg(feature = "chrono")]
     pub fn since<Tz>(
         &mut self,
@@ -1113,6 +1948,36 @@ impl LogsOptionsBuilder {
         self
     }
 
+    /// Only return logs before this time, as a UNIX timestamp
+    pub fn until(
+        &mut self,
+        timestamp: i64,
+    ) -> &mut Self {
+        self.params.insert("until", timestamp.to_string());
+        self
+    }
+
+    /// Only return logs before this time
+    #[cfg(feature = "chrono")]
+    pub fn until_datetime<Tz>(
+        &mut self,
+        timestamp: &chrono::DateTime<Tz>,
+    ) -> &mut Self
+    where
+        Tz: chrono::TimeZone,
+    {
+        self.until(timestamp.timestamp())
+    }
+
+    /// Show extra details provided to logs
+    pub fn details(
+        &mut self,
+        d: bool,
+    ) -> &mut Self {
+        self.params.insert("details", d.to_string());
+        self
+    }
+
     pub fn build(&self) -> LogsOptions {
         LogsOptions {
             params: self.params.clone(),
@@ -1176,6 +2041,160 @@ impl RmContainerOptionsBuilder {
     }
 }
 
+/// Options for extracting a tarball copied into a container
+#[derive(Default, Debug)]
+pub struct CopyToOptions {
+    params: HashMap<&'static str, String>,
+}
+
+impl CopyToOptions {
+    /// return a new instance of a builder for options
+    pub fn builder() -> CopyToOptionsBuilder {
+        CopyToOptionsBuilder::default()
+    }
+
+    /// serialize options as a string. returns None if no options are defined
+    pub fn serialize(&self) -> Option<String> {
+        if self.params.is_empty() {
+            None
+        } else {
+            Some(
+                form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(&self.params)
+                    .finish(),
+            )
+        }
+    }
+}
+
+/// Builder interface for `CopyToOptions`
+#[derive(Default)]
+pub struct CopyToOptionsBuilder {
+    params: HashMap<&'static str, String>,
+}
+
+impl CopyToOptionsBuilder {
+    /// fail instead of replacing an existing directory with a non-directory or vice versa
+    pub fn no_overwrite_dir_non_dir(
+        &mut self,
+        no_overwrite: bool,
+    ) -> &mut Self {
+        self.params
+            .insert("noOverwriteDirNonDir", no_overwrite.to_string());
+        self
+    }
+
+    /// preserve the UID/GID of the archived files instead of using the container's user
+    pub fn copy_uid_gid(
+        &mut self,
+        copy: bool,
+    ) -> &mut Self {
+        self.params.insert("copyUIDGID", copy.to_string());
+        self
+    }
+
+    pub fn build(&self) -> CopyToOptions {
+        CopyToOptions {
+            params: self.params.clone(),
+        }
+    }
+}
+
+/// Options for creating an image from a container's changes
+#[derive(Default, Debug)]
+pub struct CommitOptions {
+    params: HashMap<&'static str, String>,
+}
+
+impl CommitOptions {
+    /// return a new instance of a builder for options
+    pub fn builder() -> CommitOptionsBuilder {
+        CommitOptionsBuilder::default()
+    }
+
+    /// serialize options as a string. returns None if no options are defined
+    pub fn serialize(&self) -> Option<String> {
+        if self.params.is_empty() {
+            None
+        } else {
+            Some(
+                form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(&self.params)
+                    .finish(),
+            )
+        }
+    }
+}
+
+/// Builder interface for `CommitOptions`
+#[derive(Default)]
+pub struct CommitOptionsBuilder {
+    params: HashMap<&'static str, String>,
+}
+
+impl CommitOptionsBuilder {
+    /// Repository name for the created image
+    pub fn repo(
+        &mut self,
+        repo: &str,
+    ) -> &mut Self {
+        self.params.insert("repo", repo.to_owned());
+        self
+    }
+
+    /// Tag name for the created image
+    pub fn tag(
+        &mut self,
+        tag: &str,
+    ) -> &mut Self {
+        self.params.insert("tag", tag.to_owned());
+        self
+    }
+
+    /// Commit message
+    pub fn comment(
+        &mut self,
+        comment: &str,
+    ) -> &mut Self {
+        self.params.insert("comment", comment.to_owned());
+        self
+    }
+
+    /// Author of the image, e.g. `John Hannibal Smith <hannibal@a-team.com>`
+    pub fn author(
+        &mut self,
+        author: &str,
+    ) -> &mut Self {
+        self.params.insert("author", author.to_owned());
+        self
+    }
+
+    /// Whether to pause the container while committing. Default is true.
+    pub fn pause(
+        &mut self,
+        pause: bool,
+    ) -> &mut Self {
+        self.params.insert("pause", pause.to_string());
+        self
+    }
+
+    /// Dockerfile instructions to apply to the created image, e.g. `CMD ["nginx"]` or
+    /// `EXPOSE 80`
+    pub fn changes(
+        &mut self,
+        changes: Vec<&str>,
+    ) -> &mut Self {
+        self.params.insert("changes", changes.join("\n"));
+        self
+    }
+
+    pub fn build(&self) -> CommitOptions {
+        CommitOptions {
+            params: self.params.clone(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ContainerInfo {
@@ -1196,6 +2215,21 @@ pub struct ContainerInfo {
     pub status: String,
     pub size_rw: Option<i64>,
     pub size_root_fs: Option<i64>,
+    pub mounts: Option<Vec<Mount>>,
+    pub network_settings: Option<NetworkSettingsSummary>,
+}
+
+impl ContainerInfo {
+    /// Whether the container is known under `name`, with or without a leading `/`
+    pub fn has_name(
+        &self,
+        name: &str,
+    ) -> bool {
+        let name = normalize_name(name);
+        self.names
+            .iter()
+            .any(|candidate| normalize_name(candidate) == name)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -1240,11 +2274,16 @@ pub struct GraphDriverData {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Mount {
+    #[serde(rename = "Type")]
+    pub type_: Option<String>,
+    pub name: Option<String>,
     pub source: String,
     pub destination: String,
+    pub driver: Option<String>,
     pub mode: String,
     #[serde(rename = "RW")]
     pub rw: bool,
+    pub propagation: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -1269,6 +2308,37 @@ pub struct State {
     pub status: String,
 }
 
+impl State {
+    /// Why the container stopped, or `None` if it is still running or exited cleanly
+    ///
+    /// An OOM kill takes precedence over a daemon error, which in turn takes
+    /// precedence over the exit code the daemon reported alongside it.
+    pub fn termination_reason(&self) -> Option<TerminationReason> {
+        if self.running {
+            None
+        } else if self.oom_killed {
+            Some(TerminationReason::OomKilled)
+        } else if !self.error.is_empty() {
+            Some(TerminationReason::DaemonError(self.error.clone()))
+        } else if self.exit_code != 0 {
+            Some(TerminationReason::NonZeroExit(self.exit_code))
+        } else {
+            None
+        }
+    }
+}
+
+/// Classification of why a container stopped, see [State::termination_reason](State::termination_reason)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TerminationReason {
+    /// The kernel OOM killer terminated the container
+    OomKilled,
+    /// The container's process exited with this non-zero code
+    NonZeroExit(u64),
+    /// The daemon failed to run or manage the container
+    DaemonError(String),
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct HostConfig {
@@ -1420,10 +2490,26 @@ pub struct Port {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Stats {
     pub read: String,
+    /// Empty for containers without a network namespace of their own, e.g. `--network host`
+    #[serde(default)]
     pub networks: HashMap<String, NetworkStats>,
     pub memory_stats: MemoryStats,
     pub blkio_stats: BlkioStats,
     pub cpu_stats: CpuStats,
+    /// The cpu stats of the previous read, as reported by the daemon
+    #[serde(rename = "precpu_stats")]
+    pub pre_cpu_stats: CpuStats,
+}
+
+impl Stats {
+    /// The percentage of the host's cpu used by the container since the `previous` stats, where
+    /// 100% is one fully used cpu. See [CpuStats::cpu_percent](CpuStats::cpu_percent).
+    pub fn cpu_percent(
+        &self,
+        previous: &Stats,
+    ) -> f64 {
+        self.cpu_stats.cpu_percent(&previous.cpu_stats)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -1440,57 +2526,135 @@ pub struct NetworkStats {
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MemoryStats {
-    pub max_usage: u64,
+    /// Not reported on hosts using cgroups v2
+    pub max_usage: Option<u64>,
     pub usage: u64,
     pub failcnt: Option<u64>,
     pub limit: u64,
     pub stats: MemoryStat,
 }
 
+impl MemoryStats {
+    /// The memory used by the container excluding the page cache, as reported by `docker stats`
+    pub fn used_excluding_cache(&self) -> u64 {
+        let cache = match self.stats.cache {
+            Some(cache) => cache,
+            None => self.stats.inactive_file.unwrap_or(0),
+        };
+        self.usage.saturating_sub(cache)
+    }
+}
+
+/// Memory statistics of the container's cgroup, of which the available fields depend on the
+/// host's cgroups version
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MemoryStat {
-    pub total_pgmajfault: u64,
-    pub cache: u64,
-    pub mapped_file: u64,
-    pub total_inactive_file: u64,
-    pub pgpgout: u64,
-    pub rss: u64,
-    pub total_mapped_file: u64,
-    pub writeback: u64,
-    pub unevictable: u64,
-    pub pgpgin: u64,
-    pub total_unevictable: u64,
-    pub pgmajfault: u64,
-    pub total_rss: u64,
-    pub total_rss_huge: u64,
-    pub total_writeback: u64,
-    pub total_inactive_anon: u64,
-    pub rss_huge: u64,
-    pub hierarchical_memory_limit: u64,
-    pub hierarchical_memsw_limit: u64,
-    pub total_pgfault: u64,
-    pub total_active_file: u64,
-    pub active_anon: u64,
-    pub total_active_anon: u64,
-    pub total_pgpgout: u64,
-    pub total_cache: u64,
-    pub inactive_anon: u64,
-    pub active_file: u64,
-    pub pgfault: u64,
-    pub inactive_file: u64,
-    pub total_pgpgin: u64,
+    // cgroups v1, some of which are also reported by cgroups v2
+    pub total_pgmajfault: Option<u64>,
+    pub cache: Option<u64>,
+    pub mapped_file: Option<u64>,
+    pub total_inactive_file: Option<u64>,
+    pub pgpgout: Option<u64>,
+    pub rss: Option<u64>,
+    pub total_mapped_file: Option<u64>,
+    pub writeback: Option<u64>,
+    pub unevictable: Option<u64>,
+    pub pgpgin: Option<u64>,
+    pub total_unevictable: Option<u64>,
+    pub pgmajfault: Option<u64>,
+    pub total_rss: Option<u64>,
+    pub total_rss_huge: Option<u64>,
+    pub total_writeback: Option<u64>,
+    pub total_inactive_anon: Option<u64>,
+    pub rss_huge: Option<u64>,
+    pub hierarchical_memory_limit: Option<u64>,
+    pub hierarchical_memsw_limit: Option<u64>,
+    pub total_pgfault: Option<u64>,
+    pub total_active_file: Option<u64>,
+    pub active_anon: Option<u64>,
+    pub total_active_anon: Option<u64>,
+    pub total_pgpgout: Option<u64>,
+    pub total_cache: Option<u64>,
+    pub inactive_anon: Option<u64>,
+    pub active_file: Option<u64>,
+    pub pgfault: Option<u64>,
+    pub inactive_file: Option<u64>,
+    pub total_pgpgin: Option<u64>,
+    // cgroups v2
+    pub anon: Option<u64>,
+    pub file: Option<u64>,
+    pub kernel_stack: Option<u64>,
+    pub slab: Option<u64>,
+    pub sock: Option<u64>,
+    pub shmem: Option<u64>,
+    pub file_mapped: Option<u64>,
+    pub file_dirty: Option<u64>,
+    pub file_writeback: Option<u64>,
+    pub anon_thp: Option<u64>,
+    pub slab_reclaimable: Option<u64>,
+    pub slab_unreclaimable: Option<u64>,
+    pub workingset_refault: Option<u64>,
+    pub workingset_activate: Option<u64>,
+    pub workingset_nodereclaim: Option<u64>,
+    pub pgrefill: Option<u64>,
+    pub pgscan: Option<u64>,
+    pub pgsteal: Option<u64>,
+    pub pgactivate: Option<u64>,
+    pub pgdeactivate: Option<u64>,
+    pub pglazyfree: Option<u64>,
+    pub pglazyfreed: Option<u64>,
+    pub thp_fault_alloc: Option<u64>,
+    pub thp_collapse_alloc: Option<u64>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CpuStats {
     pub cpu_usage: CpuUsage,
+    /// Missing from the first `pre_cpu_stats` of a stream, in which case it's 0
+    #[serde(default)]
     pub system_cpu_usage: u64,
+    pub online_cpus: Option<u64>,
     pub throttling_data: ThrottlingData,
 }
 
+impl CpuStats {
+    /// The percentage of the host's cpu used since the `previous` stats, where 100% is one fully
+    /// used cpu, following the calculation of `docker stats`.
+    ///
+    /// A single [Stats](Stats) read can be used with
+    /// `stats.cpu_stats.cpu_percent(&stats.pre_cpu_stats)`.
+    pub fn cpu_percent(
+        &self,
+        previous: &CpuStats,
+    ) -> f64 {
+        let cpu_delta = self
+            .cpu_usage
+            .total_usage
+            .saturating_sub(previous.cpu_usage.total_usage);
+        let system_delta = self
+            .system_cpu_usage
+            .saturating_sub(previous.system_cpu_usage);
+        if cpu_delta == 0 || system_delta == 0 {
+            return 0.0;
+        }
+        let online_cpus = self
+            .online_cpus
+            .or_else(|| {
+                self.cpu_usage
+                    .percpu_usage
+                    .as_ref()
+                    .map(|usage| usage.len() as u64)
+            })
+            .unwrap_or(1);
+
+        cpu_delta as f64 / system_delta as f64 * online_cpus as f64 * 100.0
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CpuUsage {
-    pub percpu_usage: Vec<u64>,
+    /// Not reported on hosts using cgroups v2
+    pub percpu_usage: Option<Vec<u64>>,
     pub usage_in_usermode: u64,
     pub total_usage: u64,
     pub usage_in_kernelmode: u64,
@@ -1530,6 +2694,27 @@ pub struct Change {
     pub path: String,
 }
 
+impl Change {
+    /// The kind of change made to the path
+    pub fn change_kind(&self) -> ChangeKind {
+        match self.kind {
+            0 => ChangeKind::Modified,
+            1 => ChangeKind::Added,
+            2 => ChangeKind::Deleted,
+            kind => ChangeKind::Unknown(kind),
+        }
+    }
+}
+
+/// Kind of a [Change](Change) made to a container's filesystem
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChangeKind {
+    Modified,
+    Added,
+    Deleted,
+    Unknown(u64),
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Top {
@@ -1544,10 +2729,36 @@ pub struct ContainerCreateInfo {
     pub warnings: Option<Vec<String>>,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ContainerUpdateInfo {
+    pub warnings: Option<Vec<String>>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Exit {
     pub status_code: u64,
+    /// The reason the container could not be waited on, e.g. when it failed to start
+    #[serde(default, deserialize_with = "deserialize_exit_error")]
+    pub error: Option<String>,
+}
+
+/// Flattens the `{"Message": ".."}` object of the wait endpoint's `Error` field
+fn deserialize_exit_error<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(rename_all = "PascalCase")]
+    struct ExitError {
+        message: Option<String>,
+    }
+
+    let error = Option::<ExitError>::deserialize(deserializer)?;
+    Ok(error
+        .and_then(|e| e.message)
+        .filter(|message| !message.is_empty()))
 }
 
 #[cfg(test)]
@@ -1611,10 +2822,11 @@ mod tests {
             .network_mode("host")
             .auto_remove(true)
             .privileged(true)
+            .runtime("runsc")
             .build();
 
         assert_eq!(
-            r#"{"HostConfig":{"AutoRemove":true,"NetworkMode":"host","Privileged":true},"Image":"test_image"}"#,
+            r#"{"HostConfig":{"AutoRemove":true,"NetworkMode":"host","Privileged":true,"Runtime":"runsc"},"Image":"test_image"}"#,
             options.serialize().unwrap()
         );
     }
@@ -1659,6 +2871,99 @@ mod tests {
         );
     }
 
+    #[test]
+    fn container_options_memory() {
+        let options = ContainerOptionsBuilder::new("test_image")
+            .memory_reservation(1024)
+            .memory_swappiness(10)
+            .oom_kill_disable(true)
+            .build();
+
+        assert_eq!(
+            r#"{"HostConfig":{"MemoryReservation":1024,"MemorySwappiness":10,"OomKillDisable":true},"Image":"test_image"}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn file_archive_sets_owner_and_mode() {
+        let data = file_archive(
+            Path::new("/usr/local/bin/run.sh"),
+            b"#!/bin/sh",
+            0o755,
+            1000,
+            100,
+        )
+        .unwrap();
+        let mut archive = tar::Archive::new(data.as_slice());
+        let entry = archive.entries().unwrap().next().unwrap().unwrap();
+        let header = entry.header();
+
+        assert_eq!(
+            "usr/local/bin/run.sh",
+            entry.path().unwrap().to_str().unwrap()
+        );
+        assert_eq!(0o755, header.mode().unwrap());
+        assert_eq!(1000, header.uid().unwrap());
+        assert_eq!(100, header.gid().unwrap());
+    }
+
+    #[test]
+    fn container_options_disable_healthcheck() {
+        let options = ContainerOptionsBuilder::new("test_image")
+            .disable_healthcheck()
+            .build();
+
+        assert_eq!(
+            r#"{"Healthcheck":{"Test":["NONE"]},"HostConfig":{},"Image":"test_image"}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn container_options_cpu_realtime() {
+        let options = ContainerOptionsBuilder::new("test_image")
+            .cpu_realtime_period(1_000_000)
+            .cpu_realtime_runtime(950_000)
+            .build();
+
+        assert_eq!(
+            r#"{"HostConfig":{"CpuRealtimePeriod":1000000,"CpuRealtimeRuntime":950000},"Image":"test_image"}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn container_options_blkio() {
+        let options = ContainerOptionsBuilder::new("test_image")
+            .blkio_weight(500)
+            .blkio_device_read_bps(vec![ThrottleDevice {
+                path: "/dev/sda".to_string(),
+                rate: 1024,
+            }])
+            .build();
+
+        assert_eq!(
+            r#"{"HostConfig":{"BlkioDeviceReadBps":[{"Path":"/dev/sda","Rate":1024}],"BlkioWeight":500},"Image":"test_image"}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn container_options_storage_opt() {
+        let mut opts = HashMap::new();
+        opts.insert("size", "10G");
+
+        let options = ContainerOptionsBuilder::new("test_image")
+            .storage_opt(&opts)
+            .build();
+
+        assert_eq!(
+            r#"{"HostConfig":{"StorageOpt":{"size":"10G"}},"Image":"test_image"}"#,
+            options.serialize().unwrap()
+        );
+    }
+
     /// Test container option PublishAllPorts
     #[test]
     fn container_options_publish_all_ports() {
@@ -1749,6 +3054,56 @@ mod tests {
         assert_eq!(form, options.serialize().unwrap())
     }
 
+    #[test]
+    fn container_list_options_health_and_network() {
+        let options = ContainerListOptions::builder()
+            .filter(vec![
+                ContainerFilter::Health("unhealthy".to_string()),
+                ContainerFilter::Network("backend".to_string()),
+            ])
+            .build();
+
+        let serialized = options.serialize().unwrap();
+
+        assert!(serialized.contains("%22health%22%3A%5B%22unhealthy%22%5D"));
+        assert!(serialized.contains("%22network%22%3A%5B%22backend%22%5D"));
+    }
+
+    #[test]
+    fn container_list_options_since_before() {
+        let options = ContainerListOptions::builder()
+            .filter(vec![
+                ContainerFilter::Since("abc123".to_string()),
+                ContainerFilter::Before("def456".to_string()),
+            ])
+            .build();
+
+        let serialized = options.serialize().unwrap();
+
+        assert!(serialized.contains("%22since%22%3A%5B%22abc123%22%5D"));
+        assert!(serialized.contains("%22before%22%3A%5B%22def456%22%5D"));
+    }
+
+    #[test]
+    fn commit_options_changes() {
+        let options = CommitOptions::builder()
+            .repo("myapp")
+            .changes(vec![r#"CMD ["nginx"]"#, "EXPOSE 80"])
+            .build();
+
+        let serialized = options.serialize().unwrap();
+
+        assert!(serialized.contains("repo=myapp"));
+        assert!(serialized.contains("changes=CMD+%5B%22nginx%22%5D%0AEXPOSE+80"));
+    }
+
+    #[test]
+    fn container_list_options_limit() {
+        let options = ContainerListOptions::builder().limit(3).build();
+
+        assert_eq!("limit=3", options.serialize().unwrap())
+    }
+
     #[test]
     fn container_list_options_combined() {
         let options = ContainerListOptions::builder()
@@ -1816,4 +3171,528 @@ mod tests {
         assert!(serialized.contains("tail=all"));
         assert!(serialized.contains("since=2147483647"));
     }
+
+    #[test]
+    fn exit_error() {
+        let exit: Exit = serde_json::from_str(
+            r#"{"StatusCode":137,"Error":{"Message":"container was OOM killed"}}"#,
+        )
+        .unwrap();
+        assert_eq!(137, exit.status_code);
+        assert_eq!(Some("container was OOM killed".to_string()), exit.error);
+
+        let exit: Exit = serde_json::from_str(r#"{"StatusCode":0,"Error":null}"#).unwrap();
+        assert_eq!(None, exit.error);
+
+        let exit: Exit = serde_json::from_str(r#"{"StatusCode":0}"#).unwrap();
+        assert_eq!(None, exit.error);
+    }
+
+    #[test]
+    fn cpu_percent() {
+        let cpu_stats = |total_usage, system_cpu_usage| CpuStats {
+            cpu_usage: CpuUsage {
+                percpu_usage: Some(vec![0; 4]),
+                usage_in_usermode: 0,
+                total_usage,
+                usage_in_kernelmode: 0,
+            },
+            system_cpu_usage,
+            online_cpus: Some(2),
+            throttling_data: ThrottlingData {
+                periods: 0,
+                throttled_periods: 0,
+                throttled_time: 0,
+            },
+        };
+
+        let percent = cpu_stats(300, 2_000).cpu_percent(&cpu_stats(100, 1_000));
+        assert!((percent - 40.0).abs() < f64::EPSILON);
+        assert!(cpu_stats(100, 1_000).cpu_percent(&cpu_stats(100, 0)) == 0.0);
+    }
+
+    #[tokio::test]
+    async fn export_to_file_summary() {
+        let docker =
+            crate::docker::fake_daemon(b"HTTP/1.1 200 OK\r\nContent-Length: 11\r\n\r\nhello world");
+        let path = std::env::temp_dir().join(format!(
+            "shiplift-container-export-{}.tar",
+            std::process::id()
+        ));
+
+        let summary = docker
+            .containers()
+            .get("abc")
+            .export_to_file(&path, false)
+            .await
+            .unwrap();
+
+        let exported = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(b"hello world".to_vec(), exported);
+        assert_eq!(
+            ExportSummary {
+                bytes: 11,
+                sha256: "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".into(),
+            },
+            summary
+        );
+    }
+
+    #[tokio::test]
+    async fn copy_dir_into_missing_dir() {
+        let docker = Docker::host("http://127.0.0.1:2375".parse().unwrap());
+        let result = docker
+            .containers()
+            .get("abc")
+            .copy_dir_into(Path::new("/shiplift/does/not/exist"), Path::new("/tmp"))
+            .await;
+
+        assert!(matches!(result, Err(Error::IO(_))));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn hashing_writer() {
+        let mut out = Vec::new();
+        let mut writer = HashingWriter::new(&mut out);
+        writer.write_all(b"hello ").unwrap();
+        writer.write_all(b"world").unwrap();
+
+        assert_eq!(
+            ExportSummary {
+                bytes: 11,
+                sha256: "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".into(),
+            },
+            writer.summary()
+        );
+        assert_eq!(b"hello world".to_vec(), out);
+    }
+
+    #[test]
+    fn top_options() {
+        assert_eq!(None, TopOptions::builder().build().serialize().unwrap());
+        assert_eq!(
+            Some("ps_args=aux".to_string()),
+            TopOptions::builder()
+                .ps_args("aux")
+                .build()
+                .serialize()
+                .unwrap()
+        );
+        assert!(matches!(
+            TopOptions::builder().ps_args(" ").build().serialize(),
+            Err(Error::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            TopOptions::builder().ps_args("-ef\n").build().serialize(),
+            Err(Error::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn cpu_usage_cgroup_v2() {
+        let usage: CpuUsage = serde_json::from_str(
+            r#"{"total_usage": 100, "usage_in_kernelmode": 40, "usage_in_usermode": 60}"#,
+        )
+        .unwrap();
+
+        assert_eq!(None, usage.percpu_usage);
+    }
+
+    #[test]
+    fn memory_stats_cgroup_v2() {
+        let stats: MemoryStats = serde_json::from_str(
+            r#"{
+                "usage": 10000,
+                "limit": 100000,
+                "stats": {
+                    "active_anon": 0,
+                    "active_file": 500,
+                    "anon": 6000,
+                    "file": 3000,
+                    "inactive_file": 2500,
+                    "kernel_stack": 16384
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(None, stats.max_usage);
+        assert_eq!(Some(6000), stats.stats.anon);
+        assert_eq!(7500, stats.used_excluding_cache());
+    }
+
+    #[test]
+    fn memory_stats_cgroup_v1() {
+        let stats: MemoryStats = serde_json::from_str(
+            r#"{
+                "max_usage": 12000,
+                "usage": 10000,
+                "limit": 100000,
+                "stats": {"cache": 4000, "inactive_file": 2500, "total_rss": 6000}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(6000, stats.used_excluding_cache());
+    }
+
+    #[test]
+    fn stats_without_networks() {
+        let cpu_stats = r#"{
+            "cpu_usage": {"total_usage": 100, "usage_in_kernelmode": 40, "usage_in_usermode": 60},
+            "system_cpu_usage": 1000,
+            "online_cpus": 2,
+            "throttling_data": {"periods": 0, "throttled_periods": 0, "throttled_time": 0}
+        }"#;
+        let stats: Stats = serde_json::from_str(&format!(
+            r#"{{
+                "read": "2021-01-01T00:00:00.000000000Z",
+                "memory_stats": {{"usage": 10000, "limit": 100000, "stats": {{}}}},
+                "blkio_stats": {{
+                    "io_service_bytes_recursive": [],
+                    "io_serviced_recursive": [],
+                    "io_queue_recursive": [],
+                    "io_service_time_recursive": [],
+                    "io_wait_time_recursive": [],
+                    "io_merged_recursive": [],
+                    "io_time_recursive": [],
+                    "sectors_recursive": []
+                }},
+                "cpu_stats": {},
+                "precpu_stats": {}
+            }}"#,
+            cpu_stats, cpu_stats
+        ))
+        .unwrap();
+
+        assert!(stats.networks.is_empty());
+    }
+
+    #[test]
+    fn change_kind() {
+        let changes: Vec<Change> = serde_json::from_str(
+            r#"[{"Path":"/dev","Kind":0},{"Path":"/dev/kmsg","Kind":1},{"Path":"/test","Kind":2}]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![ChangeKind::Modified, ChangeKind::Added, ChangeKind::Deleted],
+            changes.iter().map(Change::change_kind).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn logs_options_until_details() {
+        let options = LogsOptionsBuilder::default()
+            .until(2_147_483_647)
+            .details(true)
+            .build();
+
+        let serialized = options.serialize().unwrap();
+
+        assert!(serialized.contains("until=2147483647"));
+        assert!(serialized.contains("details=true"));
+    }
+
+    #[test]
+    fn logs_options_tail_lines() {
+        let options = LogsOptionsBuilder::default().tail_lines(100).build();
+
+        assert_eq!("tail=100", options.serialize().unwrap());
+    }
+
+    #[test]
+    fn container_update_options_restart_policy() {
+        let options = ContainerUpdateOptions::builder()
+            .restart_policy("always", 0)
+            .build();
+
+        assert_eq!(
+            r#"{"RestartPolicy":{"Name":"always"}}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn container_update_options_resources() {
+        let options = ContainerUpdateOptions::builder()
+            .memory(536_870_912)
+            .cpus(1.5)
+            .restart_policy("on-failure", 3)
+            .build();
+
+        assert_eq!(
+            r#"{"Memory":536870912,"NanoCpus":1500000000,"RestartPolicy":{"MaximumRetryCount":3,"Name":"on-failure"}}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn container_update_options_blkio_weight_cpuset_mems() {
+        let options = ContainerUpdateOptions::builder()
+            .blkio_weight(300)
+            .cpuset_mems("0,1")
+            .build();
+
+        assert_eq!(
+            r#"{"BlkioWeight":300,"CpusetMems":"0,1"}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn container_info_mounts_and_networks() {
+        let info: ContainerInfo = serde_json::from_str(
+            r#"{
+                "Id": "8dfafdbc3a40",
+                "Names": ["/web"],
+                "Image": "nginx",
+                "ImageID": "sha256:d74508fb6632",
+                "Command": "nginx -g 'daemon off;'",
+                "Created": 1367854155,
+                "State": "running",
+                "Status": "Up 2 hours",
+                "Ports": [],
+                "Labels": {},
+                "NetworkSettings": {
+                    "Networks": {
+                        "bridge": {
+                            "IPAMConfig": null,
+                            "Links": null,
+                            "Aliases": null,
+                            "NetworkID": "7ea29fc1412292a2d7bba362f9253545fecdfa8ce9a6e37dd10ba8bee7129812",
+                            "EndpointID": "2cdc4edb1ded3631c81f57966563e5c8525b81121bb3706a9a9a3ae102711f3f",
+                            "Gateway": "172.17.0.1",
+                            "IPAddress": "172.17.0.2",
+                            "IPPrefixLen": 16,
+                            "IPv6Gateway": "",
+                            "GlobalIPv6Address": "",
+                            "GlobalIPv6PrefixLen": 0,
+                            "MacAddress": "02:42:ac:11:00:02"
+                        }
+                    }
+                },
+                "Mounts": [{
+                    "Type": "volume",
+                    "Name": "data",
+                    "Source": "/var/lib/docker/volumes/data/_data",
+                    "Destination": "/data",
+                    "Driver": "local",
+                    "Mode": "ro,Z",
+                    "RW": false,
+                    "Propagation": ""
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        assert!(info.has_name("web"));
+        assert!(info.has_name("/web"));
+        assert!(!info.has_name("db"));
+
+        let mounts = info.mounts.unwrap();
+        assert_eq!(1, mounts.len());
+        assert_eq!("/data", mounts[0].destination);
+        assert_eq!(
+            "172.17.0.2",
+            info.network_settings.unwrap().networks["bridge"].ip_address
+        );
+    }
+
+    #[test]
+    fn timestamp_key_pads_fraction() {
+        assert_eq!(
+            "2021-01-01T00:00:00.100000000Z",
+            timestamp_key(b"2021-01-01T00:00:00.1Z out\n")
+        );
+        assert_eq!(
+            "2021-01-01T00:00:00.000000000Z",
+            timestamp_key(b"2021-01-01T00:00:00Z out\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn merge_lines_interleaves_by_timestamp() {
+        use crate::tty::TtyChunk;
+
+        let chunks = vec![
+            TtyChunk::StdOut(b"2021-01-01T00:00:00.1Z first\n2021-01-01T00:00:00.3Z".to_vec()),
+            TtyChunk::StdErr(b"2021-01-01T00:00:00.12Z second\n".to_vec()),
+            TtyChunk::StdOut(b" third\n".to_vec()),
+        ];
+
+        let lines: Vec<Vec<u8>> =
+            merge_lines(futures_util::stream::iter(chunks.into_iter().map(Ok)))
+                .try_collect()
+                .await
+                .unwrap();
+
+        assert_eq!(
+            "2021-01-01T00:00:00.1Z first\n2021-01-01T00:00:00.12Z second\n2021-01-01T00:00:00.3Z third\n",
+            String::from_utf8(lines.concat()).unwrap()
+        );
+    }
+
+    #[test]
+    fn line_merge_releases_lines_before_end() {
+        use crate::tty::TtyChunk;
+
+        let mut merge = LineMerge::default();
+        merge.push(TtyChunk::StdOut(
+            b"2021-01-01T00:00:00.1Z a\n2021-01-01T00:00:00.3Z c\n".to_vec(),
+        ));
+        assert_eq!(None, merge.pop(false));
+
+        merge.push(TtyChunk::StdErr(b"2021-01-01T00:00:00.2Z b\n".to_vec()));
+        assert_eq!(
+            Some(b"2021-01-01T00:00:00.1Z a\n".to_vec()),
+            merge.pop(false)
+        );
+        assert_eq!(
+            Some(b"2021-01-01T00:00:00.2Z b\n".to_vec()),
+            merge.pop(false)
+        );
+        // only released once stderr is known to have nothing earlier
+        assert_eq!(None, merge.pop(false));
+        assert_eq!(
+            Some(b"2021-01-01T00:00:00.3Z c\n".to_vec()),
+            merge.pop(true)
+        );
+    }
+
+    #[test]
+    fn copy_to_options() {
+        let options = CopyToOptions::builder()
+            .no_overwrite_dir_non_dir(true)
+            .copy_uid_gid(true)
+            .build();
+        let serialized = options.serialize().unwrap();
+
+        assert!(serialized.contains("noOverwriteDirNonDir=true"));
+        assert!(serialized.contains("copyUIDGID=true"));
+        assert_eq!(None, CopyToOptions::default().serialize());
+    }
+
+    #[test]
+    fn container_options_network_aliases() {
+        let options = ContainerOptionsBuilder::new("test_image")
+            .network_aliases("app.net", vec!["web", "frontend"])
+            .network_aliases("db", vec!["client"])
+            .build();
+
+        assert_eq!(
+            r#"{"HostConfig":{},"Image":"test_image","NetworkingConfig":{"EndpointsConfig":{"app.net":{"Aliases":["web","frontend"]},"db":{"Aliases":["client"]}}}}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn normalize_container_name() {
+        assert_eq!("web", normalize_name("/web"));
+        assert_eq!("web", normalize_name("web"));
+        assert_eq!("", normalize_name("/"));
+    }
+
+    #[test]
+    fn not_found_fault() {
+        let fault = |code| Error::fault(code, "No such container: abc");
+
+        match not_found(Err(fault(StatusCode::NOT_FOUND))) {
+            Err(Error::NotFound(message)) => assert_eq!("No such container: abc", message),
+            other => panic!("expected not found, got {:?}", other),
+        }
+        assert!(matches!(
+            not_found(Err(fault(StatusCode::CONFLICT))),
+            Err(Error::Fault { .. })
+        ));
+        assert!(fault(StatusCode::NOT_FOUND).is_not_found());
+        assert!(matches!(
+            not_found_error(fault(StatusCode::NOT_FOUND)),
+            Error::NotFound(_)
+        ));
+    }
+
+    #[test]
+    fn logs_unknown_container() {
+        let stream = futures_util::stream::iter(vec![Err(Error::fault(
+            StatusCode::NOT_FOUND,
+            "No such container: typo",
+        ))]);
+        let mut logs = decode_logs(stream);
+
+        match futures::executor::block_on(futures_util::StreamExt::next(&mut logs)) {
+            Some(Err(Error::NotFound(message))) => assert_eq!("No such container: typo", message),
+            other => panic!("expected not found, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn changed_not_modified() {
+        let fault = |code| Error::fault(code, "");
+
+        assert!(matches!(
+            changed(Err(fault(StatusCode::NOT_MODIFIED))),
+            Ok(false)
+        ));
+        assert!(matches!(changed(Ok(String::new())), Ok(true)));
+        assert!(matches!(
+            changed(Err(fault(StatusCode::INTERNAL_SERVER_ERROR))),
+            Err(Error::Fault {
+                code: StatusCode::INTERNAL_SERVER_ERROR,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn ignore_fault_already_paused() {
+        let fault = |message| Error::fault(StatusCode::CONFLICT, message);
+
+        assert!(ignore_fault(
+            Err(fault("Container abc is already paused")),
+            "is already paused"
+        )
+        .is_ok());
+        assert!(ignore_fault(
+            Err(fault("Container abc is not running")),
+            "is already paused"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn state_termination_reason() {
+        let state = |running: bool, oom_killed: bool, exit_code: u64, error: &str| -> State {
+            serde_json::from_value(serde_json::json!({
+                "Error": error,
+                "ExitCode": exit_code,
+                "FinishedAt": "2021-01-01T00:00:00Z",
+                "OOMKilled": oom_killed,
+                "Paused": false,
+                "Pid": 0,
+                "Restarting": false,
+                "Running": running,
+                "StartedAt": "2021-01-01T00:00:00Z",
+                "Status": "exited"
+            }))
+            .unwrap()
+        };
+
+        assert_eq!(None, state(true, false, 0, "").termination_reason());
+        assert_eq!(None, state(false, false, 0, "").termination_reason());
+        assert_eq!(
+            Some(TerminationReason::OomKilled),
+            state(false, true, 137, "").termination_reason()
+        );
+        assert_eq!(
+            Some(TerminationReason::DaemonError("exec failed".to_owned())),
+            state(false, false, 127, "exec failed").termination_reason()
+        );
+        assert_eq!(
+            Some(TerminationReason::NonZeroExit(2)),
+            state(false, false, 2, "").termination_reason()
+        );
+    }
 }
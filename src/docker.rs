@@ -2,22 +2,26 @@
 //!
 //! API Reference: <https://docs.docker.com/engine/api/v1.41/>
 
-use std::{collections::HashMap, env, io, path::Path};
+use std::{collections::HashMap, env, path::Path, pin::Pin, sync::Arc, time::Duration};
 
-use futures_util::{stream::Stream, TryStreamExt};
-use hyper::{client::HttpConnector, Body, Client, Method};
+use futures_util::{stream::Stream, StreamExt, TryStreamExt};
+use hyper::{
+    client::{connect::Connect, HttpConnector},
+    Body, Client, Method,
+};
 use mime::Mime;
 use serde::{de, Deserialize, Serialize};
 use url::form_urlencoded;
 
 use crate::{
-    container::Containers,
+    container::{ContainerInfo, Containers},
     errors::{Error, Result},
-    image::Images,
+    image::{ImageInfo, Images, RegistryAuth},
     network::Networks,
     service::Services,
-    transport::{Headers, Payload, Transport},
-    volume::Volumes,
+    swarm::{ClusterInfo, Swarm},
+    transport::{AnyClient, Headers, Payload, Transport},
+    volume::{VolumeInfo, Volumes},
     Uri,
 };
 
@@ -28,16 +32,24 @@ use chrono::{DateTime, Utc};
 
 #[cfg(feature = "tls")]
 use hyper_openssl::HttpsConnector;
+#[cfg(feature = "proxy")]
+use hyper_proxy::{Intercept, Proxy, ProxyConnector};
 #[cfg(feature = "tls")]
 use openssl::ssl::{SslConnector, SslFiletype, SslMethod};
 
 #[cfg(feature = "unix-socket")]
 use hyperlocal::UnixConnector;
 
+#[cfg(feature = "ssh")]
+use crate::transport::SshConnector;
+
 /// Entrypoint interface for communicating with docker daemon
+///
+/// Cloning a `Docker` is cheap: clones share the same underlying client and its connection pool,
+/// so a clone can be handed to each task that needs one
 #[derive(Clone)]
 pub struct Docker {
-    transport: Transport,
+    transport: Arc<Transport>,
 }
 
 fn get_http_connector() -> HttpConnector {
@@ -78,34 +90,134 @@ fn get_docker_for_tcp(tcp_host_str: String) -> Docker {
             tcp_host_str
         };
 
+        #[cfg(feature = "proxy")]
+        if let Some(proxy) = proxy_from_env(&tcp_host_str) {
+            return get_docker_for_proxy(http, proxy, Some(connector.build()), tcp_host_str);
+        }
+
         Docker {
-            transport: Transport::EncryptedTcp {
+            transport: Arc::new(Transport::EncryptedTcp {
                 client: Client::builder()
                     .build(HttpsConnector::with_connector(http, connector).unwrap()),
                 host: tcp_host_str,
-            },
+                headers: Vec::new(),
+            }),
         }
     } else {
+        #[cfg(feature = "proxy")]
+        if let Some(proxy) = proxy_from_env(&tcp_host_str) {
+            // Proxies only forward requests for the `http` scheme
+            let tcp_host_str = tcp_host_str.replace("tcp://", "http://");
+            return get_docker_for_proxy(http, proxy, None, tcp_host_str);
+        }
+
         Docker {
-            transport: Transport::Tcp {
+            transport: Arc::new(Transport::Tcp {
                 client: Client::builder().build(http),
                 host: tcp_host_str,
-            },
+                headers: Vec::new(),
+            }),
         }
     }
 }
 
+#[cfg(feature = "proxy")]
+fn get_docker_for_proxy(
+    http: HttpConnector,
+    proxy: Uri,
+    tls: Option<SslConnector>,
+    tcp_host_str: String,
+) -> Docker {
+    let mut connector =
+        ProxyConnector::from_proxy_unsecured(http, Proxy::new(Intercept::All, proxy));
+    connector.set_tls(tls);
+
+    Docker {
+        transport: Arc::new(Transport::Proxy {
+            client: Client::builder().build(connector),
+            host: tcp_host_str,
+            headers: Vec::new(),
+        }),
+    }
+}
+
+/// Returns the proxy to use for the given docker host, following the `HTTP_PROXY`, `HTTPS_PROXY`
+/// and `NO_PROXY` env vars (or their lowercase variants)
+#[cfg(feature = "proxy")]
+fn proxy_from_env(tcp_host_str: &str) -> Option<Uri> {
+    let var = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| env::var(name).ok())
+            .filter(|value| !value.is_empty())
+    };
+
+    let host: Uri = tcp_host_str.parse().ok()?;
+    if let Some(no_proxy) = var(&["NO_PROXY", "no_proxy"]) {
+        if no_proxy_matches(&no_proxy, unbracketed(host.host()?)) {
+            return None;
+        }
+    }
+
+    let proxy = match host.scheme_str() {
+        Some("https") => var(&["HTTPS_PROXY", "https_proxy"]),
+        _ => var(&["HTTP_PROXY", "http_proxy"]),
+    };
+    proxy?.parse().ok()
+}
+
+/// The host of a url without the brackets enclosing IPv6 literals, e.g. `::1` for `[::1]`
+#[cfg(any(feature = "proxy", feature = "ssh"))]
+fn unbracketed(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+/// Whether `host` is excluded from proxying by a comma separated `NO_PROXY` list of domains
+#[cfg(feature = "proxy")]
+fn no_proxy_matches(
+    no_proxy: &str,
+    host: &str,
+) -> bool {
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.'))
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{}", entry)))
+}
+
 #[cfg(not(feature = "tls"))]
 fn get_docker_for_tcp(tcp_host_str: String) -> Docker {
     let http = get_http_connector();
     Docker {
-        transport: Transport::Tcp {
+        transport: Arc::new(Transport::Tcp {
             client: Client::builder().build(http),
             host: tcp_host_str,
-        },
+            headers: Vec::new(),
+        }),
     }
 }
 
+/// The socket path of a `unix://` url without its scheme, e.g. `/var/run/docker.sock` or the
+/// percent-encoded `%2Fvar%2Frun%2Fdocker.sock`, ignoring any query or fragment
+#[cfg(feature = "unix-socket")]
+fn unix_socket_path(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    percent_encoding::percent_decode_str(path)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+fn tcp_host_str(host: &Uri) -> String {
+    format!(
+        "{}://{}:{}",
+        host.scheme_str().unwrap(),
+        host.host().unwrap().to_owned(),
+        host.port_u16().unwrap_or(80)
+    )
+}
+
 // https://docs.docker.com/reference/api/docker_remote_api_v1.17/
 impl Docker {
     /// constructs a new Docker instance for a docker host listening at a url specified by an env var `DOCKER_HOST`,
@@ -115,7 +227,7 @@ impl Docker {
             Some(host) => {
                 #[cfg(feature = "unix-socket")]
                 if let Some(path) = host.strip_prefix("unix://") {
-                    return Docker::unix(path);
+                    return Docker::unix(unix_socket_path(path));
                 }
                 let host = host.parse().expect("invalid url");
                 Docker::host(host)
@@ -135,40 +247,122 @@ impl Docker {
         S: Into<String>,
     {
         Docker {
-            transport: Transport::Unix {
+            transport: Arc::new(Transport::Unix {
                 client: Client::builder()
                     .pool_max_idle_per_host(0)
                     .build(UnixConnector),
                 path: socket_path.into(),
-            },
+                headers: Vec::new(),
+            }),
+        }
+    }
+
+    /// Creates a new docker instance for the docker host reachable over ssh at `destination`, a
+    /// `host` or `user@host`, by running `docker system dial-stdio` on it. This requires the
+    /// `ssh` client locally and the docker cli on the remote host.
+    #[cfg(feature = "ssh")]
+    pub fn ssh<S>(
+        destination: S,
+        port: Option<u16>,
+    ) -> Docker
+    where
+        S: Into<String>,
+    {
+        let destination = destination.into();
+        Docker {
+            transport: Arc::new(Transport::Ssh {
+                client: Client::builder().build(SshConnector::new(destination.clone(), port)),
+                destination,
+                headers: Vec::new(),
+            }),
         }
     }
 
     /// constructs a new Docker instance for docker host listening at the given host url
     pub fn host(host: Uri) -> Docker {
-        let tcp_host_str = format!(
-            "{}://{}:{}",
-            host.scheme_str().unwrap(),
-            host.host().unwrap().to_owned(),
-            host.port_u16().unwrap_or(80)
-        );
+        let tcp_host_str = tcp_host_str(&host);
 
         match host.scheme_str() {
             #[cfg(feature = "unix-socket")]
             Some("unix") => Docker {
-                transport: Transport::Unix {
+                transport: Arc::new(Transport::Unix {
                     client: Client::builder().build(UnixConnector),
-                    path: host.path().to_owned(),
-                },
+                    path: unix_socket_path(host.path()),
+                    headers: Vec::new(),
+                }),
             },
 
             #[cfg(not(feature = "unix-socket"))]
             Some("unix") => panic!("Unix socket support is disabled"),
 
+            #[cfg(feature = "ssh")]
+            Some("ssh") => {
+                let authority = host.authority().expect("invalid ssh url");
+                let host = unbracketed(authority.host());
+                let destination = match authority.as_str().rsplit_once('@') {
+                    Some((user, _)) => format!("{}@{}", user, host),
+                    None => host.to_owned(),
+                };
+                Docker::ssh(destination, authority.port_u16())
+            }
+
+            #[cfg(not(feature = "ssh"))]
+            Some("ssh") => panic!("SSH support is disabled"),
+
             _ => get_docker_for_tcp(tcp_host_str),
         }
     }
 
+    /// constructs a new Docker instance for a docker host listening at the given host url,
+    /// sending requests through a client configured by the caller, e.g. with custom connection
+    /// timeouts or pool settings, or a connector of its own such as a custom DNS resolver or a
+    /// SOCKS proxy
+    pub fn from_client<C>(
+        client: Client<C>,
+        host: Uri,
+    ) -> Docker
+    where
+        C: Connect + Clone + Send + Sync + 'static,
+    {
+        Docker {
+            transport: Arc::new(Transport::Custom {
+                client: AnyClient::new(client),
+                // connectors such as `HttpConnector` refuse the `tcp` scheme
+                host: tcp_host_str(&host).replace("tcp://", "http://"),
+                headers: Vec::new(),
+            }),
+        }
+    }
+
+    /// constructs a new Docker instance for a docker host listening at the given host url,
+    /// sending requests through a TLS client configured by the caller, e.g. with custom
+    /// certificates
+    #[cfg(feature = "tls")]
+    pub fn from_tls_client(
+        client: Client<HttpsConnector<HttpConnector>>,
+        host: Uri,
+    ) -> Docker {
+        Docker {
+            transport: Arc::new(Transport::EncryptedTcp {
+                client,
+                host: tcp_host_str(&host).replace("tcp://", "https://"),
+                headers: Vec::new(),
+            }),
+        }
+    }
+
+    /// Sends the given headers along with every request made to the docker daemon, e.g. an
+    /// `Authorization` header expected by a proxy in front of it
+    pub fn with_default_headers(
+        mut self,
+        headers: Vec<(&'static str, String)>,
+    ) -> Docker {
+        Arc::make_mut(&mut self.transport)
+            .default_headers_mut()
+            .extend(headers);
+        self
+    }
+
     /// Exports an interface for interacting with docker images
     pub fn images(&'_ self) -> Images<'_> {
         Images::new(self)
@@ -184,6 +378,11 @@ impl Docker {
         Services::new(self)
     }
 
+    /// Exports an interface for interacting with the swarm
+    pub fn swarm(&'_ self) -> Swarm<'_> {
+        Swarm::new(self)
+    }
+
     pub fn networks(&'_ self) -> Networks<'_> {
         Networks::new(self)
     }
@@ -202,6 +401,44 @@ impl Docker {
         self.get_json("/info").await
     }
 
+    /// Returns the disk space used by images, containers and volumes
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/SystemDataUsage>
+    pub async fn data_usage(&self) -> Result<DataUsage> {
+        self.get_json("/system/df").await
+    }
+
+    /// Returns which images, containers and volumes are unused and how much disk space removing
+    /// them would free, computed from the [data usage](Docker::data_usage) of the daemon
+    pub async fn reclaimable(&self) -> Result<ReclaimableReport> {
+        Ok(self.data_usage().await?.reclaimable())
+    }
+
+    /// Validates credentials for a registry, returning an identity token when the registry
+    /// supports them, which can then be used with
+    /// [PullOptionsBuilder::auth_info](crate::image::PullOptionsBuilder::auth_info)
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/SystemAuth>
+    pub async fn auth(
+        &self,
+        auth: &RegistryAuth,
+    ) -> Result<AuthInfo> {
+        let body: Body = serde_json::to_string(auth)?.into();
+        self.post_json("/auth", Some((body, mime::APPLICATION_JSON)))
+            .await
+    }
+
+    /// Validates credentials for a registry like `docker login`, returning authentication to
+    /// reuse for pulling, pushing and creating images: the identity token issued by the
+    /// registry if it supports them, otherwise the given credentials
+    pub async fn login(
+        &self,
+        auth: &RegistryAuth,
+    ) -> Result<RegistryAuth> {
+        let info = self.auth(auth).await?;
+        Ok(RegistryAuth::from_auth_info(&info, auth.clone()))
+    }
+
     /// Returns a simple ping response indicating the docker daemon is accessible
     pub async fn ping(&self) -> Result<String> {
         self.get("/_ping").await
@@ -212,25 +449,87 @@ impl Docker {
         &'docker self,
         opts: &EventsOptions,
     ) -> impl Stream<Item = Result<Event>> + Unpin + 'docker {
+        self.events_with_query(opts.serialize())
+    }
+
+    /// Returns a stream of docker events which survives dropped connections
+    ///
+    /// Whenever the connection to the daemon fails or is closed, or the daemon responds with a
+    /// server error, the stream reconnects with an exponential backoff and resumes from the time
+    /// of the last event it received. Events which can't be parsed are still returned as errors.
+    /// The stream ends once the `until` time of `opts` has been reached, or after returning any
+    /// other error, e.g. a `400` fault for invalid filters.
+    ///
+    /// The backoff relies on `tokio`'s timer, so the stream must be polled from within a runtime
+    /// that has the time driver enabled.
+    pub fn events_resilient<'docker>(
+        &'docker self,
+        opts: &EventsOptions,
+    ) -> impl Stream<Item = Result<Event>> + Unpin + 'docker {
+        let state = ResilientEvents {
+            docker: self,
+            params: opts.params.clone(),
+            events: None,
+            last_seen: None,
+            backoff: RECONNECT_BACKOFF_MIN,
+            failed: false,
+        };
+
+        Box::pin(futures_util::stream::unfold(
+            state,
+            |mut state| async move {
+                if state.failed {
+                    return None;
+                }
+                loop {
+                    let events = match state.events {
+                        Some(ref mut events) => events,
+                        None => state.events.insert(state.connect()),
+                    };
+
+                    match events.next().await {
+                        Some(Ok(event)) => {
+                            let seen = event_time_nanos(&event);
+                            if matches!(state.last_seen, Some(last) if seen <= last) {
+                                // already delivered before reconnecting
+                                continue;
+                            }
+                            state.last_seen = Some(seen);
+                            state.backoff = RECONNECT_BACKOFF_MIN;
+                            return Some((Ok(event), state));
+                        }
+                        Some(Err(Error::SerdeJsonError(e))) => {
+                            return Some((Err(Error::SerdeJsonError(e)), state));
+                        }
+                        Some(Err(e)) if is_transient(&e) => {
+                            log::debug!("event stream failed, reconnecting: {}", e);
+                        }
+                        Some(Err(e)) => {
+                            state.failed = true;
+                            return Some((Err(e), state));
+                        }
+                        None if state.params.contains_key("until") => return None,
+                        None => log::debug!("event stream closed, reconnecting"),
+                    }
+
+                    state.events = None;
+                    tokio::time::sleep(state.backoff).await;
+                    state.backoff = (state.backoff * 2).min(RECONNECT_BACKOFF_MAX);
+                }
+            },
+        ))
+    }
+
+    fn events_with_query<'docker>(
+        &'docker self,
+        query: Option<String>,
+    ) -> impl Stream<Item = Result<Event>> + Unpin + Send + 'docker {
         let mut path = vec!["/events".to_owned()];
-        if let Some(query) = opts.serialize() {
+        if let Some(query) = query {
             path.push(query);
         }
-        let reader = Box::pin(
-            self.stream_get(path.join("?"))
-                .map_err(|e| io::Error::new(io::ErrorKind::Other, e)),
-        )
-        .into_async_read();
 
-        let codec = futures_codec::LinesCodec {};
-
-        Box::pin(
-            futures_codec::FramedRead::new(reader, codec)
-                .map_err(Error::IO)
-                .and_then(|s: String| async move {
-                    serde_json::from_str(&s).map_err(Error::SerdeJsonError)
-                }),
-        )
+        Box::pin(self.stream_get_into(path.join("?")))
     }
 
     //
@@ -387,6 +686,19 @@ impl Docker {
             .stream_chunks(Method::GET, endpoint, Option::<(Body, Mime)>::None, headers)
     }
 
+    /// Send a streaming get request that returns a stream of JSON values
+    ///
+    /// Values may span several chunks and a chunk may contain several values
+    pub(crate) fn stream_get_into<'a, T>(
+        &'a self,
+        endpoint: impl AsRef<str> + Unpin + 'a,
+    ) -> impl Stream<Item = Result<T>> + 'a
+    where
+        T: de::DeserializeOwned + 'a,
+    {
+        decode_json_stream(self.stream_get(endpoint))
+    }
+
     pub(crate) async fn stream_post_upgrade<'a>(
         &'a self,
         endpoint: impl AsRef<str> + 'a,
@@ -398,12 +710,117 @@ impl Docker {
     }
 }
 
+/// Decodes the JSON values contained in a stream of chunks, buffering incomplete values until the
+/// rest of them arrives
+fn decode_json_stream<'a, S, T>(chunks: S) -> impl Stream<Item = Result<T>> + 'a
+where
+    S: Stream<Item = Result<hyper::body::Bytes>> + 'a,
+    T: de::DeserializeOwned + 'a,
+{
+    chunks
+        .map(Some)
+        .chain(futures_util::stream::once(async { None }))
+        .scan(Vec::new(), |buffer, chunk| {
+            let values = match chunk {
+                Some(Ok(chunk)) => {
+                    buffer.extend_from_slice(&chunk);
+                    let mut values = Vec::new();
+                    let mut deserializer =
+                        serde_json::Deserializer::from_slice(buffer).into_iter::<T>();
+                    loop {
+                        match deserializer.next() {
+                            Some(Ok(value)) => values.push(Ok(value)),
+                            // the rest of the value is still to come
+                            Some(Err(e)) if e.is_eof() => break,
+                            Some(Err(e)) => {
+                                values.push(Err(Error::SerdeJsonError(e)));
+                                break;
+                            }
+                            None => break,
+                        }
+                    }
+                    let offset = match values.last() {
+                        // the input can't be resynchronized after malformed data
+                        Some(Err(_)) => buffer.len(),
+                        _ => deserializer.byte_offset(),
+                    };
+                    buffer.drain(..offset);
+                    values
+                }
+                Some(Err(e)) => vec![Err(e)],
+                None if buffer.iter().all(u8::is_ascii_whitespace) => Vec::new(),
+                None => vec![serde_json::from_slice(buffer).map_err(Error::SerdeJsonError)],
+            };
+            futures_util::future::ready(Some(futures_util::stream::iter(values)))
+        })
+        .flatten()
+}
+
 impl Default for Docker {
     fn default() -> Self {
         Self::new()
     }
 }
 
+const RECONNECT_BACKOFF_MIN: Duration = Duration::from_millis(100);
+const RECONNECT_BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+type EventStream<'docker> = Pin<Box<dyn Stream<Item = Result<Event>> + Send + 'docker>>;
+
+/// State of a stream returned by `Docker::events_resilient`
+struct ResilientEvents<'docker> {
+    docker: &'docker Docker,
+    params: HashMap<&'static str, String>,
+    events: Option<EventStream<'docker>>,
+    /// `timeNano` of the last event returned
+    last_seen: Option<u64>,
+    backoff: Duration,
+    /// Whether an error reconnecting won't recover from was returned, ending the stream
+    failed: bool,
+}
+
+impl<'docker> ResilientEvents<'docker> {
+    fn connect(&self) -> EventStream<'docker> {
+        let mut params = self.params.clone();
+        if let Some(last_seen) = self.last_seen {
+            params.insert(
+                "since",
+                format!(
+                    "{}.{:09}",
+                    last_seen / 1_000_000_000,
+                    last_seen % 1_000_000_000
+                ),
+            );
+        }
+
+        Box::pin(
+            self.docker
+                .events_with_query(EventsOptions { params }.serialize()),
+        )
+    }
+}
+
+/// Whether the events stream may recover from `err` by reconnecting, i.e. it's a connection
+/// failure or a server error rather than a request the daemon will keep rejecting
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::Hyper(_) | Error::IO(_) => true,
+        Error::Fault { code, .. } => code.is_server_error(),
+        _ => false,
+    }
+}
+
+#[cfg(feature = "chrono")]
+fn event_time_nanos(event: &Event) -> u64 {
+    event.time_nano.timestamp() as u64 * 1_000_000_000
+        + u64::from(event.time_nano.timestamp_subsec_nanos())
+}
+
+#[cfg(not(feature = "chrono"))]
+fn event_time_nanos(event: &Event) -> u64 {
+    event.time_nano
+}
+
 /// Options for filtering streams of Docker events
 #[derive(Default, Debug)]
 pub struct EventsOptions {
@@ -454,10 +871,14 @@ pub enum EventFilter {
     Event(String),
     Image(String),
     Label(String),
+    /// Filter by a label with a specific value, formatted as `key=value`
+    LabelKeyValue(String, String),
     Type(EventFilterType),
     Volume(String),
     Network(String),
     Daemon(String),
+    /// Only events of the given scope, `local` or `swarm`
+    Scope(String),
 }
 
 /// Builder interface for `EventOptions`
@@ -471,6 +892,7 @@ pub struct EventsOptionsBuilder {
     volumes: Vec<String>,
     networks: Vec<String>,
     daemons: Vec<String>,
+    scopes: Vec<String>,
     types: Vec<String>,
 }
 
@@ -493,6 +915,34 @@ impl EventsOptionsBuilder {
         self
     }
 
+    /// Filter events since a given point in time
+    #[cfg(feature = "chrono")]
+    pub fn since_datetime<Tz>(
+        &mut self,
+        timestamp: &chrono::DateTime<Tz>,
+    ) -> &mut Self
+    where
+        Tz: chrono::TimeZone,
+    {
+        self.params
+            .insert("since", timestamp.timestamp().to_string());
+        self
+    }
+
+    /// Filter events until a given point in time
+    #[cfg(feature = "chrono")]
+    pub fn until_datetime<Tz>(
+        &mut self,
+        timestamp: &chrono::DateTime<Tz>,
+    ) -> &mut Self
+    where
+        Tz: chrono::TimeZone,
+    {
+        self.params
+            .insert("until", timestamp.timestamp().to_string());
+        self
+    }
+
     pub fn filter(
         &mut self,
         filters: Vec<EventFilter>,
@@ -516,6 +966,10 @@ impl EventsOptionsBuilder {
                     self.labels.push(n);
                     params.insert("label", self.labels.clone())
                 }
+                EventFilter::LabelKeyValue(k, v) => {
+                    self.labels.push(format!("{}={}", k, v));
+                    params.insert("label", self.labels.clone())
+                }
                 EventFilter::Volume(n) => {
                     self.volumes.push(n);
                     params.insert("volume", self.volumes.clone())
@@ -528,6 +982,10 @@ impl EventsOptionsBuilder {
                     self.daemons.push(n);
                     params.insert("daemon", self.daemons.clone())
                 }
+                EventFilter::Scope(n) => {
+                    self.scopes.push(n);
+                    params.insert("scope", self.scopes.clone())
+                }
                 EventFilter::Type(n) => {
                     let event_type = event_filter_type_to_string(n).to_string();
                     self.types.push(event_type);
@@ -547,20 +1005,31 @@ impl EventsOptionsBuilder {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AuthInfo {
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_token: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct Version {
     pub version: String,
     pub api_version: String,
+    #[serde(default)]
     pub git_commit: String,
+    #[serde(default)]
     pub go_version: String,
     pub os: String,
     pub arch: String,
+    #[serde(default)]
     pub kernel_version: String,
     #[cfg(feature = "chrono")]
-    pub build_time: DateTime<Utc>,
+    pub build_time: Option<DateTime<Utc>>,
     #[cfg(not(feature = "chrono"))]
-    pub build_time: String,
+    pub build_time: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -570,22 +1039,171 @@ pub struct Info {
     pub images: u64,
     pub driver: String,
     pub docker_root_dir: String,
+    #[serde(default)]
     pub driver_status: Vec<Vec<String>>,
     #[serde(rename = "ID")]
     pub id: String,
+    #[serde(default)]
     pub kernel_version: String,
     // pub Labels: Option<???>,
+    #[serde(default)]
     pub mem_total: u64,
+    #[serde(default)]
     pub memory_limit: bool,
-    #[serde(rename = "NCPU")]
+    #[serde(rename = "NCPU", default)]
     pub n_cpu: u64,
+    #[serde(default)]
     pub n_events_listener: u64,
+    #[serde(default)]
     pub n_goroutines: u64,
     pub name: String,
     pub operating_system: String,
     // pub RegistryConfig:???
+    #[serde(default)]
     pub swap_limit: bool,
     pub system_time: Option<String>,
+    pub runtimes: Option<HashMap<String, Runtime>>,
+    pub default_runtime: Option<String>,
+    pub swarm: Option<NodeSwarmInfo>,
+}
+
+/// The state of the node in a swarm as reported by [Docker::info](Docker::info), see
+/// [Swarm::inspect](crate::swarm::Swarm::inspect) for the details of the swarm itself
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NodeSwarmInfo {
+    /// Empty if the node is not part of a swarm
+    #[serde(rename = "NodeID", default)]
+    pub node_id: String,
+    #[serde(default)]
+    pub node_addr: String,
+    /// `inactive`, `pending`, `active`, `error` or `locked`
+    pub local_node_state: String,
+    /// Whether the node is a manager
+    #[serde(default)]
+    pub control_available: bool,
+    #[serde(default)]
+    pub error: String,
+    /// Only reported by managers
+    pub managers: Option<u64>,
+    /// Only reported by managers
+    pub nodes: Option<u64>,
+    /// Only reported by managers
+    pub cluster: Option<ClusterInfo>,
+}
+
+/// A container runtime registered with the daemon
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Runtime {
+    pub path: Option<String>,
+    pub runtime_args: Option<Vec<String>>,
+}
+
+/// Disk space used by the objects of the daemon
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DataUsage {
+    /// Size of all image layers in bytes, shared layers being counted once
+    pub layers_size: Option<i64>,
+    pub images: Option<Vec<ImageInfo>>,
+    pub containers: Option<Vec<ContainerInfo>>,
+    pub volumes: Option<Vec<VolumeInfo>>,
+}
+
+impl DataUsage {
+    /// Computes which objects are unused and the disk space that removing them would free, the
+    /// same way `docker system df` does
+    pub fn reclaimable(&self) -> ReclaimableReport {
+        let images = self.images.as_deref().unwrap_or_default();
+        let containers = self.containers.as_deref().unwrap_or_default();
+        let volumes = self.volumes.as_deref().unwrap_or_default();
+
+        let mut image_usage = Reclaimable {
+            total: images.len(),
+            size: self.layers_size.unwrap_or_default().max(0) as u64,
+            ..Reclaimable::default()
+        };
+        let mut used = 0;
+        for image in images {
+            if image.containers.unwrap_or_default() > 0 {
+                image_usage.active += 1;
+                let size = image.size.unwrap_or_default();
+                // layers shared with other images are only counted towards the unique size
+                used += match image.shared_size {
+                    Some(shared) if shared >= 0 => size - shared,
+                    _ => size,
+                };
+            } else {
+                image_usage.unused.push(image.id.clone());
+            }
+        }
+        image_usage.reclaimable = image_usage.size.saturating_sub(used.max(0) as u64);
+
+        let mut container_usage = Reclaimable {
+            total: containers.len(),
+            ..Reclaimable::default()
+        };
+        for container in containers {
+            let size = container.size_rw.unwrap_or_default().max(0) as u64;
+            container_usage.size += size;
+            if matches!(&container.state[..], "running" | "paused" | "restarting") {
+                container_usage.active += 1;
+            } else {
+                container_usage.reclaimable += size;
+                container_usage.unused.push(container.id.clone());
+            }
+        }
+
+        let mut volume_usage = Reclaimable {
+            total: volumes.len(),
+            ..Reclaimable::default()
+        };
+        for volume in volumes {
+            // sizes and reference counts of `-1` are not available
+            let (size, ref_count) = volume
+                .usage_data
+                .as_ref()
+                .map(|usage| (usage.size.max(0) as u64, usage.ref_count))
+                .unwrap_or((0, -1));
+            volume_usage.size += size;
+            if ref_count > 0 {
+                volume_usage.active += 1;
+            } else if ref_count == 0 {
+                volume_usage.reclaimable += size;
+                volume_usage.unused.push(volume.name.clone());
+            }
+        }
+
+        ReclaimableReport {
+            images: image_usage,
+            containers: container_usage,
+            volumes: volume_usage,
+        }
+    }
+}
+
+/// Unused objects of the daemon and the disk space removing them would free
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReclaimableReport {
+    pub images: Reclaimable,
+    pub containers: Reclaimable,
+    pub volumes: Reclaimable,
+}
+
+/// Usage of one kind of object, with sizes in bytes
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Reclaimable {
+    /// Number of objects
+    pub total: usize,
+    /// Number of objects in use
+    pub active: usize,
+    /// Disk space used by all objects
+    pub size: u64,
+    /// Disk space freed by removing the unused objects
+    pub reclaimable: u64,
+    /// Ids of the unused objects, or names in the case of volumes
+    pub unused: Vec<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -612,6 +1230,20 @@ pub struct Event {
     pub time_nano: u64,
 }
 
+impl Event {
+    /// Unix seconds at which the event occurred, suitable for [EventsOptionsBuilder::since](EventsOptionsBuilder::since)
+    #[cfg(feature = "chrono")]
+    pub fn timestamp_seconds(&self) -> u64 {
+        self.time.timestamp() as u64
+    }
+
+    /// Unix seconds at which the event occurred, suitable for [EventsOptionsBuilder::since](EventsOptionsBuilder::since)
+    #[cfg(not(feature = "chrono"))]
+    pub fn timestamp_seconds(&self) -> u64 {
+        self.time
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Actor {
     #[serde(rename = "ID")]
@@ -620,8 +1252,387 @@ pub struct Actor {
     pub attributes: HashMap<String, String>,
 }
 
+/// A daemon answering a single request with `response`
+#[cfg(test)]
+pub(crate) fn fake_daemon(response: &'static [u8]) -> Docker {
+    use std::io::{Read, Write};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut socket, _) = listener.accept().unwrap();
+        let mut request = [0; 1024];
+        let _ = socket.read(&mut request).unwrap();
+        socket.write_all(response).unwrap();
+    });
+    Docker::host(format!("http://{}", addr).parse().unwrap())
+}
+
 #[cfg(test)]
 mod tests {
+    use super::{DataUsage, Event, EventFilter, EventsOptions, Info, Version};
+    use url::form_urlencoded;
+
+    #[test]
+    fn events_options_label_key_value() {
+        let options = EventsOptions::builder()
+            .filter(vec![
+                EventFilter::LabelKeyValue("com.example.app".to_string(), "web".to_string()),
+                EventFilter::Label("com.example.tier".to_string()),
+            ])
+            .build();
+
+        let form = form_urlencoded::Serializer::new(String::new())
+            .append_pair(
+                "filters",
+                r#"{"label":["com.example.app=web","com.example.tier"]}"#,
+            )
+            .finish();
+
+        assert_eq!(form, options.serialize().unwrap())
+    }
+
+    #[test]
+    fn events_options_scope() {
+        let options = EventsOptions::builder()
+            .filter(vec![EventFilter::Scope("swarm".to_string())])
+            .build();
+
+        let form = form_urlencoded::Serializer::new(String::new())
+            .append_pair("filters", r#"{"scope":["swarm"]}"#)
+            .finish();
+
+        assert_eq!(form, options.serialize().unwrap())
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn events_options_datetime() {
+        use chrono::TimeZone;
+
+        let since = chrono::Utc.timestamp_opt(1_600_000_000, 0).unwrap();
+        let until = chrono::Utc.timestamp_opt(2_147_483_647, 0).unwrap();
+
+        let options = EventsOptions::builder()
+            .since_datetime(&since)
+            .until_datetime(&until)
+            .build();
+
+        let serialized = options.serialize().unwrap();
+
+        assert!(serialized.contains("since=1600000000"));
+        assert!(serialized.contains("until=2147483647"));
+    }
+
+    #[test]
+    fn data_usage_reclaimable() {
+        let usage: DataUsage = serde_json::from_str(
+            r#"{
+                "LayersSize": 1000,
+                "Images": [
+                    {
+                        "Id": "sha256:used",
+                        "ParentId": "",
+                        "RepoTags": ["web:latest"],
+                        "RepoDigests": [],
+                        "Created": 1600000000,
+                        "Size": 600,
+                        "SharedSize": 200,
+                        "VirtualSize": 600,
+                        "Labels": {},
+                        "Containers": 2
+                    },
+                    {
+                        "Id": "sha256:dangling",
+                        "ParentId": "",
+                        "RepoTags": null,
+                        "RepoDigests": null,
+                        "Created": 1600000000,
+                        "Size": 500,
+                        "SharedSize": 200,
+                        "VirtualSize": 500,
+                        "Labels": null,
+                        "Containers": 0
+                    }
+                ],
+                "Containers": [
+                    {
+                        "Id": "running",
+                        "Names": ["/web"],
+                        "Image": "web",
+                        "ImageID": "sha256:used",
+                        "Command": "serve",
+                        "Created": 1600000000,
+                        "Ports": [],
+                        "SizeRw": 10,
+                        "Labels": {},
+                        "State": "running",
+                        "Status": "Up 2 hours"
+                    },
+                    {
+                        "Id": "exited",
+                        "Names": ["/job"],
+                        "Image": "web",
+                        "ImageID": "sha256:used",
+                        "Command": "migrate",
+                        "Created": 1600000000,
+                        "Ports": [],
+                        "SizeRw": 30,
+                        "Labels": {},
+                        "State": "exited",
+                        "Status": "Exited (0) 2 hours ago"
+                    }
+                ],
+                "Volumes": [
+                    {
+                        "CreatedAt": "2020-09-13T12:26:40Z",
+                        "Driver": "local",
+                        "Labels": null,
+                        "Mountpoint": "/var/lib/docker/volumes/data/_data",
+                        "Name": "data",
+                        "Options": null,
+                        "Scope": "local",
+                        "UsageData": {"Size": 100, "RefCount": 1}
+                    },
+                    {
+                        "CreatedAt": "2020-09-13T12:26:40Z",
+                        "Driver": "local",
+                        "Labels": null,
+                        "Mountpoint": "/var/lib/docker/volumes/cache/_data",
+                        "Name": "cache",
+                        "Options": null,
+                        "Scope": "local",
+                        "UsageData": {"Size": 50, "RefCount": 0}
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let report = usage.reclaimable();
+
+        assert_eq!(
+            (2, 1, 1000, 600),
+            (
+                report.images.total,
+                report.images.active,
+                report.images.size,
+                report.images.reclaimable
+            )
+        );
+        assert_eq!(vec!["sha256:dangling"], report.images.unused);
+        assert_eq!(
+            (2, 1, 40, 30),
+            (
+                report.containers.total,
+                report.containers.active,
+                report.containers.size,
+                report.containers.reclaimable
+            )
+        );
+        assert_eq!(vec!["exited"], report.containers.unused);
+        assert_eq!(
+            (2, 1, 150, 50),
+            (
+                report.volumes.total,
+                report.volumes.active,
+                report.volumes.size,
+                report.volumes.reclaimable
+            )
+        );
+        assert_eq!(vec!["cache"], report.volumes.unused);
+    }
+
+    #[tokio::test]
+    async fn decode_json_stream_reframes_values() {
+        use futures_util::{stream, TryStreamExt};
+        use hyper::body::Bytes;
+
+        let chunks = stream::iter(
+            vec!["{\"a\":\n1}{\"a\"", ":2}\n{\"a\":3}\n{", "\"a\":4}\n"]
+                .into_iter()
+                .map(|chunk| Ok(Bytes::from(chunk))),
+        );
+
+        let values: Vec<serde_json::Value> = super::decode_json_stream(chunks)
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                serde_json::json!({"a": 1}),
+                serde_json::json!({"a": 2}),
+                serde_json::json!({"a": 3}),
+                serde_json::json!({"a": 4}),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn decode_json_stream_truncated_value() {
+        use futures_util::{stream, StreamExt};
+        use hyper::body::Bytes;
+
+        let chunks = stream::iter(vec![Ok(Bytes::from("{\"a\":1}\n{\"a\""))]);
+
+        let values: Vec<_> = super::decode_json_stream::<_, serde_json::Value>(chunks)
+            .collect()
+            .await;
+
+        assert_eq!(2, values.len());
+        assert!(values[0].is_ok());
+        assert!(matches!(values[1], Err(crate::Error::SerdeJsonError(_))));
+    }
+
+    #[test]
+    fn default_headers() {
+        let docker = super::Docker::host("http://localhost:8000".parse().unwrap())
+            .with_default_headers(vec![("Authorization", "Bearer token".to_string())]);
+
+        assert_eq!(
+            docker.transport.default_headers(),
+            &[("Authorization", "Bearer token".to_string())]
+        );
+    }
+
+    #[test]
+    fn from_client() {
+        let client = hyper::Client::builder()
+            .pool_max_idle_per_host(1)
+            .build_http();
+        let docker = super::Docker::from_client(client, "tcp://localhost:2375".parse().unwrap());
+
+        assert_eq!(
+            "Custom(http://localhost:2375)",
+            format!("{:?}", docker.transport)
+        );
+    }
+
+    #[derive(Clone)]
+    struct FixedResolver(std::net::SocketAddr);
+
+    impl hyper::service::Service<hyper::client::connect::dns::Name> for FixedResolver {
+        type Response = std::iter::Once<std::net::SocketAddr>;
+        type Error = std::io::Error;
+        type Future = futures_util::future::Ready<std::io::Result<Self::Response>>;
+
+        fn poll_ready(
+            &mut self,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(
+            &mut self,
+            _: hyper::client::connect::dns::Name,
+        ) -> Self::Future {
+            futures_util::future::ready(Ok(std::iter::once(self.0)))
+        }
+    }
+
+    #[tokio::test]
+    async fn from_client_custom_resolver() {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let daemon = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let read = socket.read(&mut request).unwrap();
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nOK")
+                .unwrap();
+            String::from_utf8_lossy(&request[..read]).into_owned()
+        });
+
+        let client = hyper::Client::builder().build(
+            hyper::client::HttpConnector::new_with_resolver(FixedResolver(addr)),
+        );
+        let docker = super::Docker::from_client(
+            client,
+            format!("tcp://docker.invalid:{}", addr.port())
+                .parse()
+                .unwrap(),
+        );
+
+        assert_eq!("OK", docker.ping().await.unwrap());
+        assert!(daemon.join().unwrap().starts_with("GET /_ping "));
+    }
+
+    #[test]
+    fn tcp_host_str_ipv6() {
+        let host = super::tcp_host_str(&"tcp://[::1]:2375".parse().unwrap());
+        assert_eq!("tcp://[::1]:2375", host);
+        assert!(format!("{}/version", host.replace("tcp://", "http://"))
+            .parse::<crate::Uri>()
+            .is_ok());
+
+        assert_eq!(
+            "http://[fe80::1]:80",
+            super::tcp_host_str(&"http://[fe80::1]".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn clones_share_transport() {
+        let docker = super::Docker::host("http://localhost:2375".parse().unwrap());
+        let clone = docker.clone();
+
+        assert!(std::sync::Arc::ptr_eq(&docker.transport, &clone.transport));
+    }
+
+    #[cfg(feature = "proxy")]
+    #[test]
+    fn no_proxy_matches() {
+        use super::no_proxy_matches;
+
+        assert!(no_proxy_matches(
+            "localhost, .example.com",
+            "docker.example.com"
+        ));
+        assert!(no_proxy_matches("localhost,example.com", "example.com"));
+        assert!(no_proxy_matches("*", "docker.example.com"));
+        assert!(!no_proxy_matches("localhost,example.com", "badexample.com"));
+        assert!(!no_proxy_matches("", "example.com"));
+        assert!(no_proxy_matches(
+            "localhost,::1",
+            super::unbracketed("[::1]")
+        ));
+    }
+
+    #[cfg(feature = "ssh")]
+    #[test]
+    fn ssh_host() {
+        use super::Docker;
+
+        let d = Docker::host("ssh://user@example.com:2222".parse().unwrap());
+        assert_eq!("Ssh(user@example.com)", format!("{:?}", d.transport));
+        let d = Docker::host("ssh://example.com".parse().unwrap());
+        assert_eq!("Ssh(example.com)", format!("{:?}", d.transport));
+        let d = Docker::host("ssh://user@[::1]:2222".parse().unwrap());
+        assert_eq!("Ssh(user@::1)", format!("{:?}", d.transport));
+    }
+
+    #[cfg(feature = "unix-socket")]
+    #[test]
+    fn unix_socket_path() {
+        use super::unix_socket_path;
+
+        assert_eq!(
+            "/var/run/docker.sock",
+            unix_socket_path("/var/run/docker.sock")
+        );
+        assert_eq!("/run/docker.sock", unix_socket_path("%2Frun%2Fdocker.sock"));
+        assert_eq!(
+            "/run/my docker.sock",
+            unix_socket_path("/run/my%20docker.sock?timeout=1")
+        );
+    }
+
     #[cfg(feature = "unix-socket")]
     #[test]
     fn unix_host_env() {
@@ -629,7 +1640,7 @@ mod tests {
         use std::env;
         env::set_var("DOCKER_HOST", "unix:///docker.sock");
         let d = Docker::new();
-        match d.transport {
+        match &*d.transport {
             crate::transport::Transport::Unix { path, .. } => {
                 assert_eq!(path, "/docker.sock");
             }
@@ -639,7 +1650,7 @@ mod tests {
         }
         env::set_var("DOCKER_HOST", "http://localhost:8000");
         let d = Docker::new();
-        match d.transport {
+        match &*d.transport {
             crate::transport::Transport::Tcp { host, .. } => {
                 assert_eq!(host, "http://localhost:8000");
             }
@@ -648,4 +1659,148 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn info_swarm() {
+        let info = |swarm: &str| {
+            serde_json::from_str::<Info>(&format!(
+                r#"{{
+                    "Containers": 0,
+                    "Images": 0,
+                    "Driver": "overlay2",
+                    "DockerRootDir": "/var/lib/docker",
+                    "ID": "id",
+                    "Name": "host",
+                    "OperatingSystem": "Debian",
+                    "Swarm": {}
+                }}"#,
+                swarm
+            ))
+            .unwrap()
+            .swarm
+            .unwrap()
+        };
+
+        let inactive = info(
+            r#"{"NodeID": "", "NodeAddr": "", "LocalNodeState": "inactive", "ControlAvailable": false, "Error": "", "RemoteManagers": null}"#,
+        );
+        assert_eq!("inactive", inactive.local_node_state);
+        assert!(inactive.cluster.is_none());
+
+        let manager = info(
+            r#"{
+                "NodeID": "k67qz4598weg5unwwffg6z1m1",
+                "NodeAddr": "10.0.0.46",
+                "LocalNodeState": "active",
+                "ControlAvailable": true,
+                "Error": "",
+                "RemoteManagers": [{"NodeID": "k67qz4598weg5unwwffg6z1m1", "Addr": "10.0.0.46:2377"}],
+                "Nodes": 4,
+                "Managers": 3,
+                "Cluster": {
+                    "ID": "abajmipo7b4xz5ip2nrla6b11",
+                    "Version": {"Index": 373531},
+                    "CreatedAt": "2016-08-18T10:44:24.496525531Z",
+                    "UpdatedAt": "2017-08-09T07:09:37.632105588Z",
+                    "RootRotationInProgress": false,
+                    "DataPathPort": 4789,
+                    "DefaultAddrPool": ["10.0.0.0/8"],
+                    "SubnetSize": 24
+                }
+            }"#,
+        );
+        assert!(manager.control_available);
+        assert_eq!((Some(3), Some(4)), (manager.managers, manager.nodes));
+        assert_eq!("abajmipo7b4xz5ip2nrla6b11", manager.cluster.unwrap().id);
+    }
+
+    #[test]
+    fn info_optional_fields() {
+        let info: Info = serde_json::from_str(
+            r#"{
+                "Containers": 0,
+                "Images": 0,
+                "Driver": "fuse-overlayfs",
+                "DockerRootDir": "/home/user/.local/share/docker",
+                "DriverStatus": [],
+                "ID": "id",
+                "KernelVersion": "5.10.0",
+                "MemoryLimit": false,
+                "Name": "host",
+                "OperatingSystem": "Debian",
+                "SwapLimit": false,
+                "Runtimes": {
+                    "runc": {"path": "runc"},
+                    "nvidia": {"path": "nvidia-container-runtime", "runtimeArgs": ["--debug"]}
+                },
+                "DefaultRuntime": "runc"
+            }"#,
+        )
+        .unwrap();
+
+        let runtimes = info.runtimes.unwrap();
+        assert_eq!(
+            Some(vec!["--debug".to_string()]),
+            runtimes["nvidia"].runtime_args
+        );
+        assert_eq!(Some("runc".to_string()), info.default_runtime);
+
+        assert_eq!(0, info.n_cpu);
+        assert_eq!(0, info.mem_total);
+        assert_eq!(0, info.n_events_listener);
+        assert_eq!(0, info.n_goroutines);
+    }
+
+    #[test]
+    fn version_without_build_details() {
+        let version: Version = serde_json::from_str(
+            r#"{
+                "Version": "20.10.5",
+                "ApiVersion": "1.41",
+                "Os": "linux",
+                "Arch": "amd64"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!("20.10.5", version.version);
+        assert_eq!("", version.git_commit);
+        assert!(version.build_time.is_none());
+    }
+
+    #[test]
+    fn event_timestamp_seconds() {
+        let event: Event = serde_json::from_str(
+            r#"{
+                "Type": "container",
+                "Action": "start",
+                "Actor": {"ID": "abc", "Attributes": {}},
+                "time": 1609459200,
+                "timeNano": 1609459200123456789
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(1_609_459_200, event.timestamp_seconds());
+    }
+
+    #[test]
+    fn events_resilient_reconnects_on_transient_errors() {
+        use crate::Error;
+        use hyper::StatusCode;
+
+        let fault = |code| Error::fault(code, "");
+
+        assert!(super::is_transient(&Error::IO(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "connection reset"
+        ))));
+        assert!(super::is_transient(&fault(
+            StatusCode::INTERNAL_SERVER_ERROR
+        )));
+        assert!(super::is_transient(&fault(StatusCode::SERVICE_UNAVAILABLE)));
+        assert!(!super::is_transient(&fault(StatusCode::BAD_REQUEST)));
+        assert!(!super::is_transient(&fault(StatusCode::NOT_FOUND)));
+        assert!(!super::is_transient(&Error::InvalidResponse(String::new())));
+    }
 }
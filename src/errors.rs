@@ -18,9 +18,21 @@ pub enum Error {
     IO(IoError),
     Encoding(FromUtf8Error),
     InvalidResponse(String),
+    /// An argument was rejected before sending the request to the daemon
+    InvalidArgument(String),
+    /// A step of an image build failed, with the message reported by the daemon
+    BuildError(String),
+    /// The object the request was made for doesn't exist (anymore), with the message reported
+    /// by the daemon
+    NotFound(String),
+    /// The object the request was made for is still in use, e.g. a network with connected
+    /// endpoints, with the message reported by the daemon
+    Conflict(String),
     Fault {
         code: StatusCode,
         message: String,
+        /// The raw, unparsed response body returned by the daemon
+        body: String,
     },
     ConnectionNotUpgraded,
 }
@@ -77,7 +89,11 @@ impl fmt::Display for Error {
             Error::InvalidResponse(ref cause) => {
                 write!(f, "Response doesn't have the expected format: {}", cause)
             }
-            Error::Fault { code, message } => write!(f, "{}: {}", code, message),
+            Error::InvalidArgument(ref cause) => write!(f, "Invalid argument: {}", cause),
+            Error::BuildError(ref message) => write!(f, "Build failed: {}", message),
+            Error::NotFound(ref message) => write!(f, "Not found: {}", message),
+            Error::Conflict(ref message) => write!(f, "Conflict: {}", message),
+            Error::Fault { code, message, .. } => write!(f, "{}: {}", code, message),
             Error::ConnectionNotUpgraded => write!(
                 f,
                 "expected the docker host to upgrade the HTTP connection but it did not"
@@ -86,6 +102,33 @@ impl fmt::Display for Error {
     }
 }
 
+impl Error {
+    /// Whether the daemon reported that the object of the request doesn't exist, either as
+    /// [NotFound](Error::NotFound) or a `404` [Fault](Error::Fault)
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Error::NotFound(_) => true,
+            Error::Fault { code, .. } => *code == StatusCode::NOT_FOUND,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+impl Error {
+    /// A [Fault](Error::Fault) with `code` and `message` and an empty body
+    pub(crate) fn fault(
+        code: StatusCode,
+        message: &str,
+    ) -> Error {
+        Error::Fault {
+            code,
+            message: message.to_owned(),
+            body: String::new(),
+        }
+    }
+}
+
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
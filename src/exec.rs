@@ -6,6 +6,7 @@ use std::{
     collections::{BTreeMap, HashMap},
     hash::Hash,
     iter,
+    time::Duration,
 };
 
 use futures_util::{stream::Stream, TryFutureExt};
@@ -125,20 +126,43 @@ impl<'docker> Exec<'docker> {
     /// It's in callers responsibility to ensure that exec instance with specified id actually
     /// exists. Use [Exec::create](Exec::create) to ensure that the exec instance is created
     /// beforehand.
+    ///
+    /// This is the same as [Exec::from_id](Exec::from_id), which doesn't need to be awaited.
     pub async fn get<S>(
         docker: &'docker Docker,
         id: S,
     ) -> Exec<'docker>
+    where
+        S: Into<String>,
+    {
+        Exec::from_id(docker, id)
+    }
+
+    /// Get a reference to a set of operations available to an already created exec instance,
+    /// e.g. to [start](Exec::start) an exec instance previously created with
+    /// [Exec::create](Exec::create) or [Container::exec_create](crate::Container::exec_create).
+    ///
+    /// It's in callers responsibility to ensure that exec instance with specified id actually
+    /// exists.
+    pub fn from_id<S>(
+        docker: &'docker Docker,
+        id: S,
+    ) -> Exec<'docker>
     where
         S: Into<String>,
     {
         Exec::new(docker, id)
     }
 
+    /// a getter for the exec instance id
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     /// Starts this exec instance returning a multiplexed tty stream
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ExecStart)
-    pub fn start(&self) -> impl Stream<Item = Result<tty::TtyChunk>> + 'docker {
+    pub fn start(&self) -> impl Stream<Item = Result<tty::TtyChunk>> + Unpin + 'docker {
         // We must take ownership of the docker reference to not needlessly tie the stream to the
         // lifetime of `self`.
         let docker = self.docker;
@@ -159,6 +183,26 @@ impl<'docker> Exec<'docker> {
         )
     }
 
+    /// Starts this exec instance returning a [Multiplexer](crate::tty::Multiplexer) which, like
+    /// [Container::attach](crate::Container::attach), implements Stream for returning Stdout and
+    /// Stderr chunks and `[AsyncWrite]` for writing to Stdin.
+    ///
+    /// Writing to Stdin requires the exec instance to be created with `AttachStdin` enabled.
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ExecStart)
+    pub async fn start_duplex(&self) -> Result<tty::Multiplexer<'docker>> {
+        let body: Body = json!({ "Detach": false }).to_string().into();
+        let stream = self
+            .docker
+            .stream_post_upgrade(
+                format!("/exec/{}/start", &self.id),
+                Some((body, mime::APPLICATION_JSON)),
+            )
+            .await?;
+
+        Ok(tty::Multiplexer::new(stream))
+    }
+
     /// Inspect this exec instance to aquire detailed information
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ExecInpsect)
@@ -168,6 +212,21 @@ impl<'docker> Exec<'docker> {
             .await
     }
 
+    /// Polls [inspect](Exec::inspect) every `poll` until the exec instance has finished running
+    /// and returns its exit code, failing with [Error::InvalidResponse](Error::InvalidResponse)
+    /// if it was never started.
+    pub async fn wait_for_exit(
+        &self,
+        poll: Duration,
+    ) -> Result<i64> {
+        loop {
+            match self.inspect().await?.exit_status() {
+                Some(status) => return status,
+                None => tokio::time::sleep(poll).await,
+            }
+        }
+    }
+
     /// Resize the TTY session used by an exec instance. This only works if the exec was created
     /// with `tty` enabled.
     ///
@@ -191,6 +250,7 @@ impl<'docker> Exec<'docker> {
 pub struct ExecContainerOptions {
     params: HashMap<&'static str, Vec<String>>,
     params_bool: HashMap<&'static str, bool>,
+    params_str: HashMap<&'static str, String>,
 }
 
 impl ExecContainerOptions {
@@ -217,6 +277,13 @@ impl ExecContainerOptions {
             );
         }
 
+        for (k, v) in &self.params_str {
+            body.insert(
+                (*k).to_owned(),
+                serde_json::to_value(v).map_err(Error::SerdeJsonError)?,
+            );
+        }
+
         serde_json::to_string(&body).map_err(Error::from)
     }
 }
@@ -225,6 +292,7 @@ impl ExecContainerOptions {
 pub struct ExecContainerOptionsBuilder {
     params: HashMap<&'static str, Vec<String>>,
     params_bool: HashMap<&'static str, bool>,
+    params_str: HashMap<&'static str, String>,
 }
 
 impl ExecContainerOptionsBuilder {
@@ -256,6 +324,25 @@ impl ExecContainerOptionsBuilder {
         self
     }
 
+    /// Environment variables as a map of names to values, added to those set with
+    /// [env](ExecContainerOptionsBuilder::env)
+    pub fn env_map(
+        &mut self,
+        envs: &HashMap<&str, &str>,
+    ) -> &mut Self {
+        let mut envs = envs
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>();
+        // keep the serialized options stable regardless of the map's order
+        envs.sort();
+        self.params
+            .entry("Env")
+            .or_default()
+            .extend(envs);
+        self
+    }
+
     /// Attach to stdout of the exec command
     pub fn attach_stdout(
         &mut self,
@@ -274,10 +361,39 @@ impl ExecContainerOptionsBuilder {
         self
     }
 
+    /// Attach to stdin of the exec command, which can then be written to through
+    /// [Exec::start_duplex](Exec::start_duplex)
+    pub fn attach_stdin(
+        &mut self,
+        stdin: bool,
+    ) -> &mut Self {
+        self.params_bool.insert("AttachStdin", stdin);
+        self
+    }
+
+    /// Working directory inside the container to run the exec command in
+    pub fn working_dir(
+        &mut self,
+        working_dir: &str,
+    ) -> &mut Self {
+        self.params_str.insert("WorkingDir", working_dir.to_owned());
+        self
+    }
+
+    /// Key sequence for detaching from the exec command, e.g. `ctrl-p,ctrl-q`
+    pub fn detach_keys(
+        &mut self,
+        detach_keys: &str,
+    ) -> &mut Self {
+        self.params_str.insert("DetachKeys", detach_keys.to_owned());
+        self
+    }
+
     pub fn build(&self) -> ExecContainerOptions {
         ExecContainerOptions {
             params: self.params.clone(),
             params_bool: self.params_bool.clone(),
+            params_str: self.params_str.clone(),
         }
     }
 }
@@ -369,6 +485,24 @@ pub struct ExecDetails {
     pub pid: u64,
 }
 
+impl ExecDetails {
+    /// The exit code once the exec instance has finished, an error if it never started, or
+    /// `None` while it's still running
+    fn exit_status(&self) -> Option<Result<i64>> {
+        if self.running {
+            return None;
+        }
+        match self.exit_code {
+            Some(code) => Some(Ok(code as i64)),
+            None if self.pid == 0 => Some(Err(Error::InvalidResponse(format!(
+                "exec {} was never started",
+                self.id
+            )))),
+            None => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ProcessConfig {
     pub arguments: Vec<String>,
@@ -377,3 +511,72 @@ pub struct ProcessConfig {
     pub tty: bool,
     pub user: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_container_options_working_dir_and_detach_keys() {
+        let options = ExecContainerOptions::builder()
+            .cmd(vec!["ls"])
+            .working_dir("/app")
+            .detach_keys("ctrl-p,ctrl-q")
+            .build();
+
+        assert_eq!(
+            r#"{"Cmd":["ls"],"DetachKeys":"ctrl-p,ctrl-q","WorkingDir":"/app"}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn exec_container_options_env_map() {
+        let envs = [("B", "2"), ("A", "1")].iter().cloned().collect();
+        let options = ExecContainerOptions::builder()
+            .env(vec!["C=3"])
+            .env_map(&envs)
+            .build();
+
+        assert_eq!(
+            r#"{"Env":["C=3","A=1","B=2"]}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn exec_details_exit_status() {
+        let details = |running: bool, exit_code: Option<u64>, pid: u64| -> ExecDetails {
+            serde_json::from_value(json!({
+                "CanRemove": false,
+                "ContainerID": "abc",
+                "DetachKeys": "",
+                "ExitCode": exit_code,
+                "ID": "def",
+                "OpenStderr": true,
+                "OpenStdin": false,
+                "OpenStdout": true,
+                "ProcessConfig": {
+                    "arguments": [],
+                    "entrypoint": "true",
+                    "privileged": false,
+                    "tty": false,
+                    "user": null
+                },
+                "Running": running,
+                "Pid": pid
+            }))
+            .unwrap()
+        };
+
+        assert!(details(true, None, 42).exit_status().is_none());
+        assert!(matches!(
+            details(false, Some(3), 42).exit_status(),
+            Some(Ok(3))
+        ));
+        assert!(matches!(
+            details(false, None, 0).exit_status(),
+            Some(Err(Error::InvalidResponse(_)))
+        ));
+    }
+}
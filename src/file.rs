@@ -0,0 +1,147 @@
+//! Writing streamed responses to files without blocking the async runtime
+
+use flate2::{write::GzEncoder, Compression};
+use futures_util::{stream::Stream, TryStreamExt};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    thread,
+};
+use tokio::sync::{mpsc, oneshot};
+
+use crate::errors::Result;
+
+/// Number of chunks that may be buffered before streaming waits on the file being written
+const CHUNKS_IN_FLIGHT: usize = 8;
+
+/// Writes the chunks of `stream` to a new file at `path` on a separate thread, gzip compressing
+/// them if `gzip` is set, and returns the number of bytes written before compression.
+///
+/// The file is removed if the stream or writing fails, or the returned future is dropped before
+/// completing, rather than leaving a truncated file behind.
+pub(crate) async fn write_stream<S>(
+    path: &Path,
+    gzip: bool,
+    mut stream: S,
+) -> Result<u64>
+where
+    S: Stream<Item = Result<Vec<u8>>> + Unpin,
+{
+    // `None` marks the end of the stream, the channel closing without it aborts the file
+    let (sender, receiver) = mpsc::channel::<Option<Vec<u8>>>(CHUNKS_IN_FLIGHT);
+    let (done_sender, done) = oneshot::channel();
+    let path = path.to_owned();
+    thread::spawn(move || {
+        let _ = done_sender.send(write_file(path, gzip, receiver));
+    });
+
+    let mut written = 0;
+    let streamed: Result<()> = async {
+        while let Some(chunk) = stream.try_next().await? {
+            written += chunk.len() as u64;
+            if sender.send(Some(chunk)).await.is_err() {
+                // writing failed, which is reported below
+                return Ok(());
+            }
+        }
+        let _ = sender.send(None).await;
+        Ok(())
+    }
+    .await;
+    drop(sender);
+
+    let file_written = done.await.unwrap_or_else(|_| {
+        Err(io::Error::new(
+            io::ErrorKind::BrokenPipe,
+            "file writer thread panicked",
+        ))
+    });
+    streamed?;
+    file_written?;
+    Ok(written)
+}
+
+fn write_file(
+    path: PathBuf,
+    gzip: bool,
+    mut receiver: mpsc::Receiver<Option<Vec<u8>>>,
+) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(&path)?);
+    let written = if gzip {
+        let mut encoder = GzEncoder::new(&mut file, Compression::default());
+        write_chunks(&mut receiver, &mut encoder).and_then(|_| encoder.finish().map(drop))
+    } else {
+        write_chunks(&mut receiver, &mut file)
+    }
+    .and_then(|_| file.flush());
+
+    if written.is_err() {
+        drop(file);
+        let _ = fs::remove_file(&path);
+    }
+    written
+}
+
+fn write_chunks<W>(
+    receiver: &mut mpsc::Receiver<Option<Vec<u8>>>,
+    writer: &mut W,
+) -> io::Result<()>
+where
+    W: Write,
+{
+    while let Some(chunk) = receiver.blocking_recv() {
+        match chunk {
+            Some(chunk) => writer.write_all(&chunk)?,
+            None => return Ok(()),
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::Interrupted,
+        "stream ended before completion",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("shiplift-{}-{}", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn write_stream_gzip() {
+        let path = temp_path("write-stream-gzip");
+        let chunks = vec![Ok(b"hello ".to_vec()), Ok(b"world".to_vec())];
+
+        let written = write_stream(&path, true, futures_util::stream::iter(chunks))
+            .await
+            .unwrap();
+
+        let mut contents = String::new();
+        GzDecoder::new(File::open(&path).unwrap())
+            .read_to_string(&mut contents)
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!("hello world", contents);
+        assert_eq!(11, written);
+    }
+
+    #[tokio::test]
+    async fn write_stream_removes_file_on_error() {
+        let path = temp_path("write-stream-error");
+        let chunks = vec![
+            Ok(b"partial".to_vec()),
+            Err(Error::InvalidResponse("connection reset".into())),
+        ];
+
+        let result = write_stream(&path, false, futures_util::stream::iter(chunks)).await;
+
+        assert!(matches!(result, Err(Error::InvalidResponse(_))));
+        assert!(!path.exists());
+    }
+}
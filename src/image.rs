@@ -2,14 +2,27 @@
 //!
 //! API Reference: <https://docs.docker.com/engine/api/v1.41/#tag/Image>
 
-use std::{collections::HashMap, io::Read, iter};
-
-use futures_util::{stream::Stream, TryFutureExt, TryStreamExt};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    io::Read,
+    iter,
+    path::Path,
+    str::FromStr,
+};
+
+use flate2::read::GzDecoder;
+use futures_util::{stream::Stream, StreamExt, TryFutureExt, TryStreamExt};
 use hyper::Body;
 use serde::{Deserialize, Serialize};
 use url::form_urlencoded;
 
-use crate::{docker::Docker, errors::Result, tarball, transport::tar};
+use crate::{
+    docker::{AuthInfo, Docker},
+    errors::{Error, Result},
+    file, tarball,
+    transport::tar,
+};
 
 #[cfg(feature = "chrono")]
 use crate::datetime::datetime_from_unix_timestamp;
@@ -48,6 +61,16 @@ impl<'docker> Image<'docker> {
             .await
     }
 
+    /// Inspects a named image's details as raw JSON, giving access to fields not modeled by
+    /// [ImageDetails](ImageDetails)
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ImageInspect)
+    pub async fn inspect_raw(&self) -> Result<serde_json::Value> {
+        self.docker
+            .get_json(&format!("/images/{}/json", self.name)[..])
+            .await
+    }
+
     /// Lists the history of the images set of changes
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ImageHistory)
@@ -91,6 +114,18 @@ impl<'docker> Image<'docker> {
         let _ = self.docker.post(&path.join("?"), None).await?;
         Ok(())
     }
+
+    /// Adds the tag `repo:tag` to an image
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ImageTag)
+    pub async fn tag_as(
+        &self,
+        repo: &str,
+        tag: &str,
+    ) -> Result<()> {
+        self.tag(&TagOptions::builder().repo(repo).tag(tag).build())
+            .await
+    }
 }
 
 /// Interface for docker images
@@ -106,6 +141,8 @@ impl<'docker> Images<'docker> {
 
     /// Builds a new image build by reading a Dockerfile in a target directory
     ///
+    /// A failing build step ends the stream with an [Error::BuildError](Error::BuildError).
+    ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ImageBuild)
     pub fn build(
         &self,
@@ -116,11 +153,11 @@ impl<'docker> Images<'docker> {
             endpoint.push(query)
         }
 
-        // To not tie the lifetime of `opts` to the 'stream, we do the tarring work outside of the
-        // stream. But for backwards compatability, we have to return the error inside of the
-        // stream.
-        let mut bytes = Vec::default();
-        let tar_result = tarball::dir(&mut bytes, opts.path.as_str());
+        // To not tie the lifetime of `opts` to the 'stream, we check the build context outside
+        // of the stream. But for backwards compatability, we have to return the error inside of
+        // the stream. The context itself is tarred while it is being uploaded so that it never
+        // has to fit in memory.
+        let context = fs::metadata(&opts.path).map(|_| opts.path.clone());
 
         // We must take ownership of the Docker reference. If we don't then the lifetime of 'stream
         // is incorrectly tied to `self`.
@@ -128,20 +165,47 @@ impl<'docker> Images<'docker> {
         Box::pin(
             async move {
                 // Bubble up error inside the stream for backwards compatability
-                tar_result?;
+                let path = context?;
 
                 let value_stream = docker.stream_post_into(
                     endpoint.join("?"),
-                    Some((Body::from(bytes), tar())),
+                    Some((tarball::dir_body(path, true), tar())),
                     None::<iter::Empty<_>>,
                 );
 
-                Ok(value_stream)
+                Ok(value_stream.map(fail_on_build_error))
             }
             .try_flatten_stream(),
         )
     }
 
+    /// Builds an image like [build](Images::build), draining the build output and returning the
+    /// id of the resulting image.
+    ///
+    /// The full id is taken from the build output's `aux` message, falling back on inspecting
+    /// the image's [tag](BuildOptionsBuilder::tag), or as a last resort the short id of the
+    /// `Successfully built` line, for daemons which don't report it.
+    pub async fn build_and_get_id(
+        &self,
+        opts: &BuildOptions,
+    ) -> Result<String> {
+        let mut stream = self.build(opts);
+        let mut built = BuiltImageId::default();
+        while let Some(chunk) = stream.try_next().await? {
+            built.observe(&chunk);
+        }
+
+        if let Some(id) = built.aux {
+            return Ok(id);
+        }
+        match opts.params.get("t").or(built.short.as_ref()) {
+            Some(reference) => Ok(self.get(reference.as_str()).inspect().await?.id),
+            None => Err(Error::InvalidResponse(
+                "the daemon didn't report the id of the untagged image".into(),
+            )),
+        }
+    }
+
     /// Lists the docker images on the current docker host
     ///
     /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ImageList)
@@ -175,9 +239,21 @@ impl<'docker> Images<'docker> {
     pub async fn search(
         &self,
         term: &str,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_with(term, &Default::default()).await
+    }
+
+    /// Search for docker images by term, limiting and filtering the results
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ImageSearch)
+    pub async fn search_with(
+        &self,
+        term: &str,
+        opts: &SearchOptions,
     ) -> Result<Vec<SearchResult>> {
         let query = form_urlencoded::Serializer::new(String::new())
             .append_pair("term", term)
+            .extend_pairs(&opts.params)
             .finish();
         self.docker
             .get_json::<Vec<SearchResult>>(&format!("/images/search?{}", query)[..])
@@ -219,6 +295,24 @@ impl<'docker> Images<'docker> {
             .map_ok(|c| c.to_vec())
     }
 
+    /// exports a collection of named images into a tarball written to the file at `path`,
+    /// gzip-compressing it on the fly if `gzip` is set. The file is removed again if the export
+    /// fails.
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ImageGetAll)
+    pub async fn export_to_file<P>(
+        &self,
+        names: Vec<&str>,
+        path: P,
+        gzip: bool,
+    ) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        file::write_stream(path.as_ref(), gzip, Box::pin(self.export(names))).await?;
+        Ok(())
+    }
+
     /// imports an image or set of images from a given tarball source
     /// source can be uncompressed on compressed via gzip, bzip2 or xz
     ///
@@ -246,6 +340,50 @@ impl<'docker> Images<'docker> {
             .try_flatten_stream(),
         )
     }
+
+    /// imports an image or set of images from a given compressed tarball source, decompressing
+    /// it according to `format` before sending it to the daemon
+    ///
+    /// [Api Reference](https://docs.docker.com/engine/api/v1.41/#operation/ImageLoad)
+    pub fn import_compressed<R>(
+        self,
+        tarball: R,
+        format: Compression,
+    ) -> impl Stream<Item = Result<ImageBuildChunk>> + Unpin + 'docker
+    where
+        R: Read + Send + 'docker,
+    {
+        self.import(decompress(tarball, format))
+    }
+}
+
+/// Compression formats of tarballs given to
+/// [Images::import_compressed](Images::import_compressed)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    None,
+    Gzip,
+    #[cfg(feature = "bzip2")]
+    Bzip2,
+    #[cfg(feature = "xz2")]
+    Xz,
+}
+
+fn decompress<'a, R>(
+    reader: R,
+    format: Compression,
+) -> Box<dyn Read + Send + 'a>
+where
+    R: Read + Send + 'a,
+{
+    match format {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(GzDecoder::new(reader)),
+        #[cfg(feature = "bzip2")]
+        Compression::Bzip2 => Box::new(bzip2::read::BzDecoder::new(reader)),
+        #[cfg(feature = "xz2")]
+        Compression::Xz => Box::new(xz2::read::XzDecoder::new(reader)),
+    }
 }
 
 #[derive(Clone, Serialize, Debug)]
@@ -279,6 +417,17 @@ impl RegistryAuth {
         }
     }
 
+    /// return the identity token of `info` if the registry returned one, otherwise `credentials`
+    pub fn from_auth_info(
+        info: &AuthInfo,
+        credentials: RegistryAuth,
+    ) -> RegistryAuth {
+        match info.identity_token {
+            Some(ref token) if !token.is_empty() => RegistryAuth::token(token.as_str()),
+            _ => credentials,
+        }
+    }
+
     /// return a new instance of a builder for authentication
     pub fn builder() -> RegistryAuthBuilder {
         RegistryAuthBuilder::default()
@@ -355,6 +504,137 @@ impl RegistryAuthBuilder {
     }
 }
 
+/// Registry of images referenced without one
+const DEFAULT_REGISTRY: &str = "docker.io";
+
+/// Namespace of the official images of the [default registry](DEFAULT_REGISTRY)
+const OFFICIAL_NAMESPACE: &str = "library";
+
+/// An image reference of the form `[registry/][namespace/]name[:tag][@digest]`, e.g.
+/// `ghcr.io/softprops/shiplift:latest`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reference {
+    /// Host, with an optional port, of the registry holding the image
+    pub registry: Option<String>,
+    /// Path of the repository below the registry, without its last component
+    pub namespace: Option<String>,
+    pub name: String,
+    pub tag: Option<String>,
+    /// Content addressable digest, e.g. `sha256:...`
+    pub digest: Option<String>,
+}
+
+impl Reference {
+    /// Fills in the defaults the daemon assumes, turning e.g. `nginx` into
+    /// `docker.io/library/nginx:latest`
+    pub fn normalized(&self) -> Reference {
+        let registry = self
+            .registry
+            .clone()
+            .unwrap_or_else(|| DEFAULT_REGISTRY.to_owned());
+        let namespace = match self.namespace {
+            None if registry == DEFAULT_REGISTRY => Some(OFFICIAL_NAMESPACE.to_owned()),
+            ref namespace => namespace.clone(),
+        };
+        let tag = match self.tag {
+            None if self.digest.is_none() => Some("latest".to_owned()),
+            ref tag => tag.clone(),
+        };
+        Reference {
+            registry: Some(registry),
+            namespace,
+            name: self.name.clone(),
+            tag,
+            digest: self.digest.clone(),
+        }
+    }
+
+    /// The registry holding the image, i.e. the `serveraddress` to
+    /// [authenticate](RegistryAuthBuilder::server_address) against
+    pub fn registry_or_default(&self) -> &str {
+        self.registry.as_deref().unwrap_or(DEFAULT_REGISTRY)
+    }
+
+    /// The repository of the image including its namespace and registry, without tag or digest
+    pub fn repository(&self) -> String {
+        self.registry
+            .iter()
+            .chain(self.namespace.iter())
+            .chain(iter::once(&self.name))
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+impl FromStr for Reference {
+    type Err = Error;
+
+    fn from_str(reference: &str) -> Result<Reference> {
+        let invalid = || Error::InvalidArgument(format!("invalid image reference {}", reference));
+
+        let (rest, digest) = match reference.split_once('@') {
+            Some((rest, digest)) if digest.contains(':') => (rest, Some(digest.to_owned())),
+            Some(_) => return Err(invalid()),
+            None => (reference, None),
+        };
+        // a colon after the last slash separates the tag, any other one a registry port
+        let (path, tag) = match rest.rfind(':') {
+            Some(index) if !rest[index..].contains('/') => {
+                (&rest[..index], Some(rest[index + 1..].to_owned()))
+            }
+            _ => (rest, None),
+        };
+
+        let mut components = path.split('/').collect::<Vec<_>>();
+        let registry = match components[..] {
+            [first, _, ..]
+                if first.contains('.') || first.contains(':') || first == "localhost" =>
+            {
+                Some(components.remove(0).to_owned())
+            }
+            _ => None,
+        };
+        let name = components.pop().unwrap_or_default().to_owned();
+        let namespace = if components.is_empty() {
+            None
+        } else {
+            Some(components.join("/"))
+        };
+
+        if name.is_empty()
+            || components.iter().any(|component| component.is_empty())
+            || tag.as_deref() == Some("")
+        {
+            return Err(invalid());
+        }
+
+        Ok(Reference {
+            registry,
+            namespace,
+            name,
+            tag,
+            digest,
+        })
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        write!(f, "{}", self.repository())?;
+        if let Some(ref tag) = self.tag {
+            write!(f, ":{}", tag)?;
+        }
+        if let Some(ref digest) = self.digest {
+            write!(f, "@{}", digest)?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct TagOptions {
     pub params: HashMap<&'static str, String>,
@@ -447,6 +727,7 @@ impl PullOptions {
 
 pub struct PullOptionsBuilder {
     auth: Option<RegistryAuth>,
+    all_tags: bool,
     params: HashMap<&'static str, String>,
 }
 
@@ -455,7 +736,11 @@ impl Default for PullOptionsBuilder {
         let mut params = HashMap::new();
         params.insert("tag", "latest".to_string());
 
-        PullOptionsBuilder { auth: None, params }
+        PullOptionsBuilder {
+            auth: None,
+            all_tags: false,
+            params,
+        }
     }
 }
 
@@ -516,6 +801,16 @@ impl PullOptionsBuilder {
         self
     }
 
+    /// Pull all tags of the given image. When enabled no `tag` is sent, even if one was set
+    /// with [tag](PullOptionsBuilder::tag), instead of the default `latest` tag.
+    pub fn all_tags(
+        &mut self,
+        all_tags: bool,
+    ) -> &mut Self {
+        self.all_tags = all_tags;
+        self
+    }
+
     pub fn auth(
         &mut self,
         auth: RegistryAuth,
@@ -524,10 +819,24 @@ impl PullOptionsBuilder {
         self
     }
 
+    /// Authenticate with the identity token returned by [Docker::auth](crate::Docker::auth),
+    /// falling back to `credentials` if the registry didn't return one
+    pub fn auth_info(
+        &mut self,
+        info: &AuthInfo,
+        credentials: RegistryAuth,
+    ) -> &mut Self {
+        self.auth(RegistryAuth::from_auth_info(info, credentials))
+    }
+
     pub fn build(&mut self) -> PullOptions {
+        let mut params = self.params.clone();
+        if self.all_tags {
+            params.remove("tag");
+        }
         PullOptions {
             auth: self.auth.take(),
-            params: self.params.clone(),
+            params,
         }
     }
 }
@@ -536,6 +845,8 @@ impl PullOptionsBuilder {
 pub struct BuildOptions {
     pub path: String,
     params: HashMap<&'static str, String>,
+    /// parameters which are repeated for each of their values
+    params_list: HashMap<&'static str, Vec<String>>,
 }
 
 impl BuildOptions {
@@ -551,12 +862,17 @@ impl BuildOptions {
 
     /// serialize options as a string. returns None if no options are defined
     pub fn serialize(&self) -> Option<String> {
-        if self.params.is_empty() {
+        if self.params.is_empty() && self.params_list.is_empty() {
             None
         } else {
             Some(
                 form_urlencoded::Serializer::new(String::new())
                     .extend_pairs(&self.params)
+                    .extend_pairs(
+                        self.params_list
+                            .iter()
+                            .flat_map(|(k, values)| values.iter().map(move |v| (k, v))),
+                    )
                     .finish(),
             )
         }
@@ -567,6 +883,7 @@ impl BuildOptions {
 pub struct BuildOptionsBuilder {
     path: String,
     params: HashMap<&'static str, String>,
+    params_list: HashMap<&'static str, Vec<String>>,
 }
 
 impl BuildOptionsBuilder {
@@ -670,6 +987,46 @@ impl BuildOptionsBuilder {
         self
     }
 
+    /// images to use as cache sources, e.g. previously pushed builds of the same image
+    pub fn cache_from(
+        &mut self,
+        images: Vec<&str>,
+    ) -> &mut Self {
+        self.params
+            .insert("cachefrom", serde_json::to_string(&images).unwrap());
+        self
+    }
+
+    /// squash the layers created by the build into a single new layer
+    pub fn squash(
+        &mut self,
+        squash: bool,
+    ) -> &mut Self {
+        self.params.insert("squash", squash.to_string());
+        self
+    }
+
+    /// hosts to add to `/etc/hosts` of the build containers, in the form "host:ip"
+    pub fn extra_hosts(
+        &mut self,
+        hosts: Vec<&str>,
+    ) -> &mut Self {
+        self.params_list.insert(
+            "extrahosts",
+            hosts.into_iter().map(ToOwned::to_owned).collect(),
+        );
+        self
+    }
+
+    /// suppress the verbose build output
+    pub fn quiet(
+        &mut self,
+        quiet: bool,
+    ) -> &mut Self {
+        self.params.insert("q", quiet.to_string());
+        self
+    }
+
     // todo: memswap
     // todo: cpusetcpus
     // todo: cpuperiod
@@ -680,6 +1037,41 @@ impl BuildOptionsBuilder {
         BuildOptions {
             path: self.path.clone(),
             params: self.params.clone(),
+            params_list: self.params_list.clone(),
+        }
+    }
+}
+
+/// Turns the error message the daemon streams for a failed build step into an `Err`
+fn fail_on_build_error(chunk: Result<ImageBuildChunk>) -> Result<ImageBuildChunk> {
+    match chunk {
+        Ok(ImageBuildChunk::Error { error, .. }) => Err(Error::BuildError(error)),
+        chunk => chunk,
+    }
+}
+
+/// The ids of the built image reported in the build output
+#[derive(Default, Debug)]
+struct BuiltImageId {
+    /// The full id of the `aux` message of recent daemons
+    aux: Option<String>,
+    /// The short id of the final `Successfully built` line of older daemons
+    short: Option<String>,
+}
+
+impl BuiltImageId {
+    fn observe(
+        &mut self,
+        chunk: &ImageBuildChunk,
+    ) {
+        match chunk {
+            ImageBuildChunk::Digest { aux } => self.aux = Some(aux.id.clone()),
+            ImageBuildChunk::Update { stream } => {
+                if let Some(short) = stream.trim_end().strip_prefix("Successfully built ") {
+                    self.short = Some(short.to_owned());
+                }
+            }
+            _ => (),
         }
     }
 }
@@ -689,6 +1081,8 @@ pub enum ImageFilter {
     Dangling,
     LabelName(String),
     Label(String, String),
+    /// Only images with a reference matching the pattern, e.g. `nginx:*`
+    Reference(String),
 }
 
 /// Options for filtering image list results
@@ -742,16 +1136,112 @@ impl ImageListOptionsBuilder {
         self
     }
 
+    /// Adds `filters` to those of previous calls and [reference](ImageListOptionsBuilder::reference)
     pub fn filter(
         &mut self,
         filters: Vec<ImageFilter>,
+    ) -> &mut Self {
+        // structure is a a json encoded object mapping string keys to a list
+        // of string values
+        let mut param: BTreeMap<String, Vec<String>> = self
+            .params
+            .get("filters")
+            .and_then(|filters| serde_json::from_str(filters).ok())
+            .unwrap_or_default();
+        for f in filters {
+            let (key, value) = match f {
+                ImageFilter::Dangling => ("dangling", true.to_string()),
+                ImageFilter::LabelName(n) => ("label", n),
+                ImageFilter::Label(n, v) => ("label", format!("{}={}", n, v)),
+                ImageFilter::Reference(r) => ("reference", r),
+            };
+            let values = param.entry(key.to_owned()).or_default();
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+        self.params
+            .insert("filters", serde_json::to_string(&param).unwrap());
+        self
+    }
+
+    /// Only list images with a reference matching `pattern`, e.g. `nginx:*`. Unlike
+    /// [filter_name](ImageListOptionsBuilder::filter_name), this uses the `filters` parameter
+    /// supported by current API versions and is kept alongside the other filters.
+    pub fn reference(
+        &mut self,
+        pattern: &str,
+    ) -> &mut Self {
+        self.filter(vec![ImageFilter::Reference(pattern.to_owned())])
+    }
+
+    pub fn build(&self) -> ImageListOptions {
+        ImageListOptions {
+            params: self.params.clone(),
+        }
+    }
+}
+
+/// Filter options for image searches
+pub enum SearchFilter {
+    IsOfficial(bool),
+    IsAutomated(bool),
+    /// Only return results with at least this many stars
+    Stars(u64),
+}
+
+/// Options for limiting and filtering image search results
+#[derive(Default, Debug)]
+pub struct SearchOptions {
+    params: HashMap<&'static str, String>,
+}
+
+impl SearchOptions {
+    /// return a new instance of a builder for options
+    pub fn builder() -> SearchOptionsBuilder {
+        SearchOptionsBuilder::default()
+    }
+
+    /// serialize options as a string. returns None if no options are defined
+    pub fn serialize(&self) -> Option<String> {
+        if self.params.is_empty() {
+            None
+        } else {
+            Some(
+                form_urlencoded::Serializer::new(String::new())
+                    .extend_pairs(&self.params)
+                    .finish(),
+            )
+        }
+    }
+}
+
+/// Builder interface for `SearchOptions`
+#[derive(Default)]
+pub struct SearchOptionsBuilder {
+    params: HashMap<&'static str, String>,
+}
+
+impl SearchOptionsBuilder {
+    /// Maximum number of search results to return
+    pub fn limit(
+        &mut self,
+        limit: u64,
+    ) -> &mut Self {
+        self.params.insert("limit", limit.to_string());
+        self
+    }
+
+    pub fn filter(
+        &mut self,
+        filters: Vec<SearchFilter>,
     ) -> &mut Self {
         let mut param = HashMap::new();
         for f in filters {
             match f {
-                ImageFilter::Dangling => param.insert("dangling", vec![true.to_string()]),
-                ImageFilter::LabelName(n) => param.insert("label", vec![n]),
-                ImageFilter::Label(n, v) => param.insert("label", vec![format!("{}={}", n, v)]),
+                SearchFilter::IsOfficial(o) => param.insert("is-official", vec![o.to_string()]),
+                SearchFilter::IsAutomated(a) => param.insert("is-automated", vec![a.to_string()]),
+                SearchFilter::Stars(s) => param.insert("stars", vec![s.to_string()]),
             };
         }
         // structure is a a json encoded object mapping string keys to a list
@@ -761,8 +1251,8 @@ impl ImageListOptionsBuilder {
         self
     }
 
-    pub fn build(&self) -> ImageListOptions {
-        ImageListOptions {
+    pub fn build(&self) -> SearchOptions {
+        SearchOptions {
             params: self.params.clone(),
         }
     }
@@ -791,6 +1281,12 @@ pub struct ImageInfo {
     pub repo_tags: Option<Vec<String>>,
     pub repo_digests: Option<Vec<String>>,
     pub virtual_size: u64,
+    /// Size of the image in bytes, including the layers shared with other images
+    pub size: Option<i64>,
+    /// Size of the layers shared with other images in bytes, or `-1` if not computed
+    pub shared_size: Option<i64>,
+    /// Number of containers using the image, or `-1` if not computed
+    pub containers: Option<i64>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -836,6 +1332,22 @@ pub struct ContainerConfig {
     pub tty: bool,
     pub user: String,
     pub working_dir: String,
+    pub healthcheck: Option<HealthConfig>,
+    pub stop_signal: Option<String>,
+    pub stop_timeout: Option<u64>,
+    pub volumes: Option<HashMap<String, HashMap<String, String>>>,
+    pub shell: Option<Vec<String>>,
+}
+
+/// A test to perform to check that the container is healthy, durations being in nanoseconds
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct HealthConfig {
+    pub test: Option<Vec<String>>,
+    pub interval: Option<u64>,
+    pub timeout: Option<u64>,
+    pub retries: Option<u64>,
+    pub start_period: Option<u64>,
 }
 
 impl ContainerConfig {
@@ -861,6 +1373,10 @@ pub struct History {
     #[cfg(not(feature = "chrono"))]
     pub created: u64,
     pub created_by: String,
+    /// Size of the layer in bytes
+    pub size: i64,
+    pub tags: Option<Vec<String>>,
+    pub comment: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -914,6 +1430,206 @@ pub struct ProgressDetail {
 mod tests {
     use super::*;
 
+    #[test]
+    fn image_list_options_reference_before_filter() {
+        let reference_first = ImageListOptions::builder()
+            .reference("nginx:*")
+            .filter(vec![ImageFilter::Label("env".into(), "prod".into())])
+            .build();
+        let filter_first = ImageListOptions::builder()
+            .filter(vec![ImageFilter::Label("env".into(), "prod".into())])
+            .reference("nginx:*")
+            .build();
+
+        assert_eq!(
+            Some(
+                form_urlencoded::Serializer::new(String::new())
+                    .append_pair(
+                        "filters",
+                        r#"{"label":["env=prod"],"reference":["nginx:*"]}"#
+                    )
+                    .finish()
+            ),
+            reference_first.serialize()
+        );
+        assert_eq!(reference_first.serialize(), filter_first.serialize());
+    }
+
+    #[test]
+    fn image_list_options_reference() {
+        let options = ImageListOptions::builder()
+            .filter(vec![ImageFilter::Dangling])
+            .reference("nginx:*")
+            .build();
+
+        assert_eq!(
+            Some(
+                form_urlencoded::Serializer::new(String::new())
+                    .append_pair(
+                        "filters",
+                        r#"{"dangling":["true"],"reference":["nginx:*"]}"#
+                    )
+                    .finish()
+            ),
+            options.serialize()
+        );
+    }
+
+    #[test]
+    fn build_options_cache_from() {
+        let options = BuildOptions::builder(".")
+            .cache_from(vec!["web:latest", "web:cache"])
+            .build();
+
+        assert_eq!(
+            Some(
+                form_urlencoded::Serializer::new(String::new())
+                    .append_pair("cachefrom", r#"["web:latest","web:cache"]"#)
+                    .finish()
+            ),
+            options.serialize()
+        );
+    }
+
+    #[test]
+    fn build_options_squash_extra_hosts_quiet() {
+        let options = BuildOptions::builder(".")
+            .squash(true)
+            .extra_hosts(vec!["registry:10.0.0.1", "cache:10.0.0.2"])
+            .quiet(true)
+            .build();
+        let serialized = options.serialize().unwrap();
+
+        assert!(serialized.contains("squash=true"));
+        assert!(serialized.contains("extrahosts=registry%3A10.0.0.1&extrahosts=cache%3A10.0.0.2"));
+        assert!(serialized.contains("q=true"));
+    }
+
+    #[test]
+    fn reference_parse() {
+        let reference: Reference = "localhost:5000/team/tools/web:1.2@sha256:abc"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            Reference {
+                registry: Some("localhost:5000".into()),
+                namespace: Some("team/tools".into()),
+                name: "web".into(),
+                tag: Some("1.2".into()),
+                digest: Some("sha256:abc".into()),
+            },
+            reference
+        );
+        assert_eq!(
+            "localhost:5000/team/tools/web:1.2@sha256:abc",
+            reference.to_string()
+        );
+
+        let reference: Reference = "softprops/shiplift".parse().unwrap();
+        assert_eq!(None, reference.registry);
+        assert_eq!(Some("softprops".into()), reference.namespace);
+        assert_eq!("docker.io", reference.registry_or_default());
+
+        assert!("".parse::<Reference>().is_err());
+        assert!("nginx:".parse::<Reference>().is_err());
+        assert!("team//web".parse::<Reference>().is_err());
+        assert!("nginx@abc".parse::<Reference>().is_err());
+    }
+
+    #[test]
+    fn reference_normalized() {
+        let normalized = |reference: &str| {
+            reference
+                .parse::<Reference>()
+                .unwrap()
+                .normalized()
+                .to_string()
+        };
+
+        assert_eq!("docker.io/library/nginx:latest", normalized("nginx"));
+        assert_eq!(
+            "docker.io/softprops/shiplift:0.7",
+            normalized("softprops/shiplift:0.7")
+        );
+        assert_eq!("ghcr.io/web:latest", normalized("ghcr.io/web"));
+        assert_eq!(
+            "docker.io/library/nginx@sha256:abc",
+            normalized("nginx@sha256:abc")
+        );
+    }
+
+    #[test]
+    fn image_history() {
+        let history: Vec<History> = serde_json::from_str(
+            r#"[
+                {
+                    "Id": "3db9c44f45209632d6050b35958829c3a2aa256d81b9a7be45b362ff85c54710",
+                    "Created": 1398108230,
+                    "CreatedBy": "/bin/sh -c #(nop) ADD file:eb15dbd63394e063b805a3c32ca7bf0266ef64676d5a6fab4801f2e81e2a5148 in /",
+                    "Tags": ["ubuntu:lucid", "ubuntu:10.04"],
+                    "Size": 182964289,
+                    "Comment": ""
+                },
+                {
+                    "Id": "<missing>",
+                    "Created": 1398108222,
+                    "CreatedBy": "/bin/sh -c #(nop) MAINTAINER Tianon Gravi <admwiggin@gmail.com> - mkimage-debootstrap.sh -i iproute,iputils-ping,ubuntu-minimal -t lucid.tar.xz lucid http://archive.ubuntu.com/ubuntu/",
+                    "Tags": null,
+                    "Size": 0,
+                    "Comment": ""
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(182_964_289, history[0].size);
+        assert_eq!(Some(2), history[0].tags.as_ref().map(Vec::len));
+        assert_eq!(None, history[1].tags);
+    }
+
+    #[test]
+    fn built_image_id_prefers_aux() {
+        let chunk = |json| serde_json::from_str::<ImageBuildChunk>(json).unwrap();
+        let mut built = BuiltImageId::default();
+        for json in &[
+            r#"{"stream":"Step 1/2 : FROM scratch\n"}"#,
+            r#"{"aux":{"ID":"sha256:4a1f5c0ffee5"}}"#,
+            r#"{"stream":"Successfully built 4a1f5c0ffee5\n"}"#,
+            r#"{"stream":"Successfully tagged app:latest\n"}"#,
+        ] {
+            built.observe(&chunk(json));
+        }
+
+        assert_eq!(Some("sha256:4a1f5c0ffee5"), built.aux.as_deref());
+        assert_eq!(Some("4a1f5c0ffee5"), built.short.as_deref());
+    }
+
+    #[test]
+    fn built_image_id_short_only() {
+        let mut built = BuiltImageId::default();
+        built.observe(
+            &serde_json::from_str(r#"{"stream":"Successfully built 4a1f5c0ffee5\n"}"#).unwrap(),
+        );
+
+        assert_eq!(None, built.aux);
+        assert_eq!(Some("4a1f5c0ffee5"), built.short.as_deref());
+    }
+
+    #[test]
+    fn build_error_chunk() {
+        let chunk: ImageBuildChunk = serde_json::from_str(
+            r#"{"errorDetail":{"code":1,"message":"exit code: 1"},"error":"exit code: 1"}"#,
+        )
+        .unwrap();
+        match fail_on_build_error(Ok(chunk)) {
+            Err(Error::BuildError(message)) => assert_eq!("exit code: 1", message),
+            other => panic!("expected a build error, got {:?}", other),
+        }
+
+        let chunk: ImageBuildChunk = serde_json::from_str(r#"{"stream":"Step 1/2"}"#).unwrap();
+        assert!(fail_on_build_error(Ok(chunk)).is_ok());
+    }
+
     /// Test registry auth with token
     #[test]
     fn registry_auth_token() {
@@ -924,6 +1640,30 @@ mod tests {
         );
     }
 
+    /// Test registry auth preferring the identity token returned by `/auth`
+    #[test]
+    fn registry_auth_from_auth_info() {
+        let credentials = || {
+            RegistryAuth::builder()
+                .username("user_abc")
+                .password("password_abc")
+                .build()
+        };
+        let info = |identity_token: Option<&str>| AuthInfo {
+            status: "Login Succeeded".into(),
+            identity_token: identity_token.map(String::from),
+        };
+
+        assert_eq!(
+            base64::encode(r#"{"identitytoken":"abc"}"#),
+            RegistryAuth::from_auth_info(&info(Some("abc")), credentials()).serialize()
+        );
+        assert_eq!(
+            credentials().serialize(),
+            RegistryAuth::from_auth_info(&info(None), credentials()).serialize()
+        );
+    }
+
     /// Test registry auth with username and password
     #[test]
     fn registry_auth_password_simple() {
@@ -953,4 +1693,138 @@ mod tests {
             options.serialize()
         );
     }
+
+    #[test]
+    fn search_options() {
+        let options = SearchOptions::builder()
+            .limit(10)
+            .filter(vec![
+                SearchFilter::IsOfficial(true),
+                SearchFilter::Stars(100),
+            ])
+            .build();
+
+        let serialized = options.serialize().unwrap();
+
+        assert!(serialized.contains("limit=10"));
+        assert!(serialized.contains("filters="));
+        assert!(serialized.contains("%22is-official%22%3A%5B%22true%22%5D"));
+        assert!(serialized.contains("%22stars%22%3A%5B%22100%22%5D"));
+    }
+
+    #[test]
+    fn container_config_healthcheck() {
+        let config: ContainerConfig = serde_json::from_str(
+            r#"{
+                "AttachStderr": false,
+                "AttachStdin": false,
+                "AttachStdout": false,
+                "Cmd": ["nginx", "-g", "daemon off;"],
+                "Domainname": "",
+                "Hostname": "",
+                "Image": "nginx",
+                "OpenStdin": false,
+                "StdinOnce": false,
+                "Tty": false,
+                "User": "",
+                "WorkingDir": "",
+                "Healthcheck": {
+                    "Test": ["CMD-SHELL", "curl -f http://localhost/ || exit 1"],
+                    "Interval": 30000000000,
+                    "Retries": 3
+                },
+                "StopSignal": "SIGQUIT",
+                "StopTimeout": 20,
+                "Volumes": {"/var/cache/nginx": {}},
+                "Shell": ["/bin/bash", "-c"]
+            }"#,
+        )
+        .unwrap();
+
+        let healthcheck = config.healthcheck.unwrap();
+        assert_eq!(Some(30_000_000_000), healthcheck.interval);
+        assert_eq!(Some(3), healthcheck.retries);
+        assert_eq!(None, healthcheck.timeout);
+        assert_eq!(Some("SIGQUIT".to_string()), config.stop_signal);
+        assert_eq!(Some(20), config.stop_timeout);
+        assert!(config.volumes.unwrap().contains_key("/var/cache/nginx"));
+        assert_eq!(
+            Some(vec!["/bin/bash".to_string(), "-c".to_string()]),
+            config.shell
+        );
+    }
+
+    #[test]
+    fn pull_options_all_tags() {
+        let options = PullOptions::builder()
+            .image("nginx")
+            .tag("alpine")
+            .all_tags(true)
+            .build();
+        assert_eq!(Some("fromImage=nginx".to_string()), options.serialize());
+
+        let options = PullOptions::builder()
+            .image("nginx")
+            .all_tags(false)
+            .build()
+            .serialize()
+            .unwrap();
+        assert!(options.contains("tag=latest"));
+    }
+
+    #[test]
+    fn decompress_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"tarball").unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let mut decompressed = Vec::new();
+        decompress(compressed.as_slice(), Compression::Gzip)
+            .read_to_end(&mut decompressed)
+            .unwrap();
+
+        assert_eq!(b"tarball".to_vec(), decompressed);
+    }
+
+    fn export_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("shiplift-{}-{}.tar", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn export_to_file_gzip() {
+        let docker =
+            crate::docker::fake_daemon(b"HTTP/1.1 200 OK\r\nContent-Length: 7\r\n\r\ntarball");
+        let path = export_path("export-gzip");
+
+        docker
+            .images()
+            .export_to_file(vec!["app:latest"], &path, true)
+            .await
+            .unwrap();
+
+        let mut exported = Vec::new();
+        GzDecoder::new(fs::File::open(&path).unwrap())
+            .read_to_end(&mut exported)
+            .unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(b"tarball".to_vec(), exported);
+    }
+
+    #[tokio::test]
+    async fn export_to_file_removes_partial_file() {
+        // the connection is closed before the announced body has been sent
+        let docker =
+            crate::docker::fake_daemon(b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\ntar");
+        let path = export_path("export-partial");
+
+        let result = docker
+            .images()
+            .export_to_file(vec!["app:latest"], &path, false)
+            .await;
+
+        assert!(result.is_err());
+        assert!(!path.exists());
+    }
 }
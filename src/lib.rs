@@ -27,10 +27,15 @@ pub mod exec;
 pub mod image;
 pub mod network;
 pub mod service;
+pub mod swarm;
 pub mod volume;
 
+mod file;
 mod tarball;
 
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
 #[cfg(feature = "chrono")]
 mod datetime;
 
@@ -38,20 +43,22 @@ pub use hyper::Uri;
 
 pub use crate::{
     container::{
-        Container, ContainerFilter, ContainerListOptions, ContainerOptions, Containers,
-        LogsOptions, RmContainerOptions,
+        CommitOptions, Container, ContainerFilter, ContainerListOptions, ContainerOptions,
+        ContainerUpdateOptions, Containers, CopyToOptions, ExportSummary, LogsOptions,
+        RmContainerOptions, TerminationReason, TopOptions,
     },
     docker::{Docker, EventsOptions},
     errors::{Error, Result},
     exec::{Exec, ExecContainerOptions, ExecResizeOptions},
     image::{
         BuildOptions, Image, ImageFilter, ImageListOptions, Images, PullOptions, RegistryAuth,
-        TagOptions,
+        SearchFilter, SearchOptions, TagOptions,
     },
     network::{
         ContainerConnectionOptions, Network, NetworkCreateOptions, NetworkListOptions, Networks,
     },
     service::{Service, ServiceFilter, ServiceListOptions, ServiceOptions, Services},
+    swarm::{Swarm, SwarmInitOptions, SwarmJoinOptions},
     transport::Transport,
     volume::{Volume, VolumeCreateOptions, Volumes},
 };
@@ -7,7 +7,8 @@ use std::{
     hash::Hash,
 };
 
-use hyper::Body;
+use futures_util::future::try_join_all;
+use hyper::{Body, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use url::form_urlencoded;
@@ -17,6 +18,9 @@ use crate::{
     errors::{Error, Result},
 };
 
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+
 /// Interface for docker network
 ///
 /// API Reference: <https://docs.docker.com/engine/api/v1.41/#tag/Network>
@@ -32,6 +36,9 @@ impl<'docker> Networks<'docker> {
 
     /// List the docker networks on the current docker host
     ///
+    /// The listed details include each network's scope, IPAM, internal, attachable and IPv6
+    /// flags, but not the containers connected to it, see [Network::inspect](Network::inspect).
+    ///
     /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/NetworkList>
     pub async fn list(
         &self,
@@ -106,13 +113,42 @@ impl<'docker> Network<'docker> {
             .await
     }
 
-    /// Delete the network instance
+    /// Returns the DNS aliases registered on the endpoint of each container connected to the
+    /// network, keyed by container id.
+    ///
+    /// The network inspection doesn't report aliases, so each connected container is inspected,
+    /// concurrently. The load balancer endpoints of overlay networks and containers removed in
+    /// the meantime are left out.
+    pub async fn aliases(&self) -> Result<HashMap<String, Vec<String>>> {
+        let details = self.inspect().await?;
+        let inspections = details
+            .containers
+            .keys()
+            .filter(|id| !is_load_balancer(id))
+            .map(|id| async move {
+                let container = self.docker.containers().get(id).inspect().await;
+                skip_not_found(container).map(|container| container.map(|c| (id, c)))
+            });
+
+        let mut aliases = HashMap::new();
+        for (id, container) in try_join_all(inspections).await?.into_iter().flatten() {
+            if let Some(entry) = container.network_settings.networks.get(&details.name) {
+                aliases.insert(id.clone(), entry.aliases.clone().unwrap_or_default());
+            }
+        }
+        Ok(aliases)
+    }
+
+    /// Delete the network instance, failing with [Error::Conflict](Error::Conflict) while
+    /// containers are still connected to it
     ///
     /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/NetworkDelete>
     pub async fn delete(&self) -> Result<()> {
-        self.docker
-            .delete(&format!("/networks/{}", self.id)[..])
-            .await?;
+        in_use(
+            self.docker
+                .delete(&format!("/networks/{}", self.id)[..])
+                .await,
+        )?;
         Ok(())
     }
 
@@ -239,6 +275,54 @@ impl NetworkCreateOptionsBuilder {
         self
     }
 
+    /// The level at which the network exists, either `swarm`, `global` or `local`
+    pub fn scope(
+        &mut self,
+        scope: &str,
+    ) -> &mut Self {
+        self.params.insert("Scope", json!(scope));
+        self
+    }
+
+    /// Create the network as the swarm's ingress network, providing the routing mesh
+    pub fn ingress(
+        &mut self,
+        ingress: bool,
+    ) -> &mut Self {
+        self.params.insert("Ingress", json!(ingress));
+        self
+    }
+
+    /// Subnet of the network in CIDR notation, e.g. `172.20.0.0/16`
+    pub fn subnet(
+        &mut self,
+        cidr: &str,
+    ) -> &mut Self {
+        self.ipam_config().insert("Subnet".to_owned(), json!(cidr));
+        self
+    }
+
+    /// IPv4 or IPv6 gateway for the [subnet](NetworkCreateOptionsBuilder::subnet)
+    pub fn gateway(
+        &mut self,
+        ip: &str,
+    ) -> &mut Self {
+        self.ipam_config().insert("Gateway".to_owned(), json!(ip));
+        self
+    }
+
+    /// The single entry of `IPAM.Config` set by `subnet` and `gateway`
+    fn ipam_config(&mut self) -> &mut serde_json::Map<String, Value> {
+        let ipam = self
+            .params
+            .entry("IPAM")
+            .or_insert_with(|| json!({ "Config": [{}] }));
+        match ipam["Config"][0] {
+            Value::Object(ref mut config) => config,
+            _ => unreachable!("IPAM config is only set by the builder"),
+        }
+    }
+
     pub fn build(&self) -> NetworkCreateOptions {
         NetworkCreateOptions {
             params: self.params.clone(),
@@ -330,6 +414,13 @@ pub struct NetworkSettings {
     pub networks: HashMap<String, NetworkEntry>,
 }
 
+/// Networks of a container as reported when listing containers
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NetworkSettingsSummary {
+    pub networks: HashMap<String, NetworkEntry>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct NetworkEntry {
@@ -387,14 +478,39 @@ pub struct NetworkDetails {
     pub ipam: Ipam,
     pub internal: bool,
     pub attachable: bool,
+    /// Only populated when inspecting a single network
+    #[serde(default)]
     pub containers: HashMap<String, NetworkContainerDetails>,
     pub options: Option<HashMap<String, String>>,
     pub labels: Option<HashMap<String, String>>,
+    #[cfg(feature = "chrono")]
+    pub created: Option<DateTime<Utc>>,
+    #[cfg(not(feature = "chrono"))]
+    pub created: Option<String>,
+    pub ingress: Option<bool>,
+    pub config_only: Option<bool>,
+    pub config_from: Option<ConfigReference>,
+    pub peers: Option<Vec<PeerInfo>>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConfigReference {
+    pub network: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PeerInfo {
+    pub name: String,
+    #[serde(rename = "IP")]
+    pub ip: String,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct NetworkContainerDetails {
+    pub name: Option<String>,
     #[serde(rename = "EndpointID")]
     pub endpoint_id: String,
     pub mac_address: String,
@@ -410,3 +526,135 @@ pub struct NetworkCreateInfo {
     pub id: String,
     pub warning: String,
 }
+
+/// Whether `id`, a key of [NetworkDetails::containers](NetworkDetails::containers), is the
+/// load balancer endpoint of an overlay network rather than a container
+fn is_load_balancer(id: &str) -> bool {
+    id.starts_with("lb-")
+}
+
+/// Treats an object which doesn't exist (anymore) as absent rather than an error
+fn skip_not_found<T>(response: Result<T>) -> Result<Option<T>> {
+    match response {
+        Ok(value) => Ok(Some(value)),
+        Err(e) if e.is_not_found() => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Maps the `403` fault the daemon responds with for networks that still have active endpoints
+/// to [Error::Conflict](Error::Conflict), leaving other `403`s such as those for pre-defined
+/// networks untouched
+fn in_use(response: Result<String>) -> Result<String> {
+    match response {
+        Err(Error::Fault {
+            code: StatusCode::FORBIDDEN,
+            message,
+            ..
+        }) if message.contains("has active endpoints") => Err(Error::Conflict(message)),
+        response => response,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn network_create_options_subnet_gateway() {
+        let options = NetworkCreateOptions::builder("web")
+            .subnet("172.20.0.0/16")
+            .gateway("172.20.0.1")
+            .build();
+
+        assert_eq!(
+            r#"{"Config":[{"Gateway":"172.20.0.1","Subnet":"172.20.0.0/16"}]}"#,
+            serde_json::to_string(&options.params["IPAM"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn in_use_fault() {
+        let fault = |code| {
+            Error::fault(
+                code,
+                "error while removing network: network web id abc has active endpoints",
+            )
+        };
+
+        match in_use(Err(fault(StatusCode::FORBIDDEN))) {
+            Err(Error::Conflict(message)) => assert_eq!(
+                "error while removing network: network web id abc has active endpoints",
+                message
+            ),
+            other => panic!("expected conflict, got {:?}", other),
+        }
+        assert!(matches!(
+            in_use(Err(fault(StatusCode::NOT_FOUND))),
+            Err(Error::Fault { .. })
+        ));
+    }
+
+    #[test]
+    fn aliases_skip_load_balancer_and_removed_containers() {
+        assert!(is_load_balancer("lb-my-overlay"));
+        assert!(!is_load_balancer(
+            "4c3a1c1e5ae1d3c5a0b5f9c3e1f6d2b7a8c9d0e1f2a3b4c5d6e7f8091a2b3c4d"
+        ));
+
+        let fault = |code| Error::fault(code, "No such container: lb-my-overlay");
+        assert!(matches!(
+            skip_not_found::<()>(Err(fault(StatusCode::NOT_FOUND))),
+            Ok(None)
+        ));
+        assert!(matches!(skip_not_found(Ok(1)), Ok(Some(1))));
+        assert!(skip_not_found::<()>(Err(fault(StatusCode::INTERNAL_SERVER_ERROR))).is_err());
+    }
+
+    #[test]
+    fn in_use_fault_predefined_network() {
+        let fault = Error::fault(
+            StatusCode::FORBIDDEN,
+            "bridge is a pre-defined network and cannot be removed",
+        );
+
+        assert!(matches!(
+            in_use(Err(fault)),
+            Err(Error::Fault {
+                code: StatusCode::FORBIDDEN,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn network_list_details() {
+        let networks: Vec<NetworkDetails> = serde_json::from_str(
+            r#"[{
+                "Name": "overlay",
+                "Id": "7d86d31b1478e7cca9ebed7e73aa0fdeec46c5ca29497431d3007d2d9e15ed99",
+                "Created": "2016-10-19T06:21:00.416543526Z",
+                "Scope": "swarm",
+                "Driver": "overlay",
+                "EnableIPv6": true,
+                "Internal": true,
+                "Attachable": true,
+                "Ingress": false,
+                "IPAM": {
+                    "Driver": "default",
+                    "Config": [{"Subnet": "10.0.0.0/24", "Gateway": "10.0.0.1"}]
+                },
+                "Options": {},
+                "Labels": {}
+            }]"#,
+        )
+        .unwrap();
+
+        let network = &networks[0];
+        assert_eq!("swarm", network.scope);
+        assert!(network.enable_ipv6);
+        assert!(network.internal);
+        assert!(network.attachable);
+        assert!(network.containers.is_empty());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! API Reference: <https://docs.docker.com/engine/api/v1.41/#tag/Service>
 
-use std::{collections::HashMap, iter};
+use std::{collections::HashMap, iter, time::Duration};
 
 use futures_util::stream::Stream;
 use hyper::Body;
@@ -114,6 +114,59 @@ impl<'docker> Service<'docker> {
             .await
     }
 
+    /// Inspects a named service's details, filling in the default values of any field not set
+    /// in its spec. Use this as the base of the full spec an update has to send.
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/ServiceInspect>
+    pub async fn inspect_with_defaults(&self) -> Result<ServiceDetails> {
+        self.docker
+            .get_json(&format!("/services/{}?insertDefaults=true", self.name)[..])
+            .await
+    }
+
+    /// Lists the tasks of the service, e.g. to find the nodes its replicas were scheduled on
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/TaskList>
+    pub async fn tasks(&self) -> Result<Vec<TaskInfo>> {
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("filters", &json!({ "service": [&self.name] }).to_string())
+            .finish();
+
+        self.docker.get_json(&format!("/tasks?{}", query)[..]).await
+    }
+
+    /// Rolls the service back to its previous spec, which the daemon retains after an update.
+    ///
+    /// `version` is the current version of the service, as found in
+    /// [ServiceDetails::version](ServiceDetails::version), to prevent conflicting writes.
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/ServiceUpdate>
+    pub async fn rollback(
+        &self,
+        version: u64,
+    ) -> Result<()> {
+        // The update endpoint requires a spec even when rolling back, the raw one is used as
+        // `ServiceSpec` doesn't model every field
+        let details: Value = self
+            .docker
+            .get_json(&format!("/services/{}", self.name)[..])
+            .await?;
+        let body: Body = serde_json::to_string(&details["Spec"])?.into();
+
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("version", &version.to_string())
+            .append_pair("rollback", "previous")
+            .finish();
+
+        self.docker
+            .post(
+                &format!("/services/{}/update?{}", self.name, query),
+                Some((body, mime::APPLICATION_JSON)),
+            )
+            .await?;
+        Ok(())
+    }
+
     /// Deletes a service
     ///
     /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/ServiceDelete>
@@ -290,6 +343,24 @@ impl ServiceOptionsBuilder {
         self
     }
 
+    /// Run `replicas` tasks of the service, a shortcut for a replicated [mode](Self::mode)
+    pub fn replicas(
+        &mut self,
+        replicas: u64,
+    ) -> &mut Self {
+        self.params.insert(
+            "Mode",
+            Ok(json!({ "Replicated": { "Replicas": replicas } })),
+        );
+        self
+    }
+
+    /// Run one task of the service on every node, a shortcut for a global [mode](Self::mode)
+    pub fn global(&mut self) -> &mut Self {
+        self.params.insert("Mode", Ok(json!({ "Global": {} })));
+        self
+    }
+
     pub fn update_config(
         &mut self,
         conf: &UpdateConfig,
@@ -382,6 +453,68 @@ pub struct ServiceInfo {
     pub job_status: Option<JobStatus>,
 }
 
+impl ServiceInfo {
+    /// Whether as many tasks are running as desired. This requires the service status, see
+    /// [ServiceListOptionsBuilder::enable_status](ServiceListOptionsBuilder::enable_status).
+    pub fn is_converged(&self) -> bool {
+        self.service_status
+            .as_ref()
+            .map(|status| status.running_tasks == status.desired_tasks)
+            .unwrap_or(false)
+    }
+}
+
+/// A task of a service, i.e. one of its containers scheduled on a node
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskInfo {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub version: ObjectVersion,
+    #[cfg(feature = "chrono")]
+    pub created_at: DateTime<Utc>,
+    #[cfg(not(feature = "chrono"))]
+    pub created_at: String,
+    #[cfg(feature = "chrono")]
+    pub updated_at: DateTime<Utc>,
+    #[cfg(not(feature = "chrono"))]
+    pub updated_at: String,
+    pub name: Option<String>,
+    pub labels: Option<HashMap<String, String>>,
+    #[serde(rename = "ServiceID")]
+    pub service_id: String,
+    /// Not set for tasks of global services
+    pub slot: Option<u64>,
+    /// Not set until the task has been scheduled
+    #[serde(rename = "NodeID")]
+    pub node_id: Option<String>,
+    pub status: TaskStatus,
+    pub desired_state: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskStatus {
+    #[cfg(feature = "chrono")]
+    pub timestamp: Option<DateTime<Utc>>,
+    #[cfg(not(feature = "chrono"))]
+    pub timestamp: Option<String>,
+    pub state: String,
+    pub message: Option<String>,
+    pub err: Option<String>,
+    pub container_status: Option<TaskContainerStatus>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TaskContainerStatus {
+    #[serde(rename = "ContainerID")]
+    pub container_id: Option<String>,
+    #[serde(rename = "PID")]
+    pub pid: Option<i64>,
+    pub exit_code: Option<i64>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct ObjectVersion {
@@ -400,16 +533,76 @@ pub struct Endpoint {
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct EndpointSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ports: Option<Vec<EndpointPortConfig>>,
 }
 
+impl EndpointSpec {
+    /// return a new instance of a builder for an endpoint spec
+    pub fn builder() -> EndpointSpecBuilder {
+        EndpointSpecBuilder::default()
+    }
+}
+
+#[derive(Default)]
+pub struct EndpointSpecBuilder {
+    mode: Option<String>,
+    ports: Vec<EndpointPortConfig>,
+}
+
+impl EndpointSpecBuilder {
+    /// The mode of resolution to use for internal load balancing between tasks, either `vip`
+    /// (the default) or `dnsrr`
+    pub fn mode(
+        &mut self,
+        mode: &str,
+    ) -> &mut Self {
+        self.mode = Some(mode.to_owned());
+        self
+    }
+
+    /// Publish the `target` port of the service's tasks on the `published` port, with
+    /// `protocol` being one of `tcp`, `udp` or `sctp` and `publish_mode` either `ingress` to use
+    /// the routing mesh or `host` to publish directly on the nodes running the tasks
+    pub fn port(
+        &mut self,
+        protocol: &str,
+        target: u64,
+        published: u64,
+        publish_mode: &str,
+    ) -> &mut Self {
+        self.ports.push(EndpointPortConfig {
+            name: None,
+            protocol: protocol.to_owned(),
+            publish_mode: publish_mode.to_owned(),
+            published_port: Some(published),
+            target_port: target,
+        });
+        self
+    }
+
+    pub fn build(&self) -> EndpointSpec {
+        EndpointSpec {
+            mode: self.mode.clone(),
+            ports: if self.ports.is_empty() {
+                None
+            } else {
+                Some(self.ports.clone())
+            },
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct EndpointPortConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     pub protocol: String,
     pub publish_mode: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub published_port: Option<u64>,
     pub target_port: u64,
 }
@@ -472,7 +665,7 @@ pub struct ServiceDetails {
 #[serde(rename_all = "PascalCase")]
 pub struct ServiceSpec {
     pub name: String,
-    pub labels: Option<serde_json::Value>,
+    pub labels: Option<HashMap<String, String>>,
     pub task_template: TaskSpec,
     pub mode: Mode,
     pub update_config: Option<UpdateConfig>,
@@ -515,12 +708,101 @@ pub struct UpdateConfig {
     pub delay: u64,
     pub failure_action: String,
     pub monitor: u64,
-    pub max_failure_ratio: usize,
+    /// Fraction of tasks which may fail during an update, e.g. `0.1`
+    pub max_failure_ratio: f64,
     pub order: String,
 }
 
 pub type RollbackConfig = UpdateConfig;
 
+impl UpdateConfig {
+    /// return a new instance of a builder for an update or rollback config, starting from the
+    /// daemon's defaults
+    pub fn builder() -> UpdateConfigBuilder {
+        UpdateConfigBuilder::default()
+    }
+}
+
+pub struct UpdateConfigBuilder {
+    config: UpdateConfig,
+}
+
+impl Default for UpdateConfigBuilder {
+    fn default() -> Self {
+        UpdateConfigBuilder {
+            config: UpdateConfig {
+                parallelism: 1,
+                delay: 0,
+                failure_action: "pause".to_owned(),
+                monitor: Duration::from_secs(5).as_nanos() as u64,
+                max_failure_ratio: 0.0,
+                order: "stop-first".to_owned(),
+            },
+        }
+    }
+}
+
+impl UpdateConfigBuilder {
+    /// Maximum number of tasks updated simultaneously, `0` updating all at once
+    pub fn parallelism(
+        &mut self,
+        parallelism: u64,
+    ) -> &mut Self {
+        self.config.parallelism = parallelism;
+        self
+    }
+
+    /// Time between updating each batch of tasks
+    pub fn delay(
+        &mut self,
+        delay: Duration,
+    ) -> &mut Self {
+        self.config.delay = delay.as_nanos() as u64;
+        self
+    }
+
+    /// Action on a failed task update, one of `continue`, `pause` or `rollback`
+    pub fn failure_action(
+        &mut self,
+        action: &str,
+    ) -> &mut Self {
+        self.config.failure_action = action.to_owned();
+        self
+    }
+
+    /// Time to monitor each updated task for failures
+    pub fn monitor(
+        &mut self,
+        monitor: Duration,
+    ) -> &mut Self {
+        self.config.monitor = monitor.as_nanos() as u64;
+        self
+    }
+
+    /// Fraction of tasks which may fail during an update before the failure action applies,
+    /// e.g. `0.1` for 10%
+    pub fn max_failure_ratio(
+        &mut self,
+        ratio: f64,
+    ) -> &mut Self {
+        self.config.max_failure_ratio = ratio;
+        self
+    }
+
+    /// Order of operations when rolling out a task, either `stop-first` or `start-first`
+    pub fn order(
+        &mut self,
+        order: &str,
+    ) -> &mut Self {
+        self.config.order = order.to_owned();
+        self
+    }
+
+    pub fn build(&self) -> UpdateConfig {
+        self.config.clone()
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct NetworkAttachmentConfig {
@@ -536,3 +818,136 @@ pub struct ServiceCreateInfo {
     #[serde(rename = "Warning")]
     pub warning: Option<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn service_options_endpoint_spec() {
+        let options = ServiceOptions::builder()
+            .endpoint_spec(
+                &EndpointSpec::builder()
+                    .mode("vip")
+                    .port("tcp", 80, 8080, "ingress")
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            r#"{"EndpointSpec":{"Mode":"vip","Ports":[{"Protocol":"tcp","PublishMode":"ingress","PublishedPort":8080,"TargetPort":80}]}}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn service_options_mode_shortcuts() {
+        let options = ServiceOptions::builder().replicas(3).build().unwrap();
+        assert_eq!(
+            r#"{"Mode":{"Replicated":{"Replicas":3}}}"#,
+            options.serialize().unwrap()
+        );
+
+        let options = ServiceOptions::builder().global().build().unwrap();
+        assert_eq!(r#"{"Mode":{"Global":{}}}"#, options.serialize().unwrap());
+    }
+
+    #[test]
+    fn update_config_builder() {
+        let options = ServiceOptions::builder()
+            .update_config(
+                &UpdateConfig::builder()
+                    .parallelism(2)
+                    .delay(Duration::from_secs(10))
+                    .max_failure_ratio(0.1)
+                    .order("start-first")
+                    .build(),
+            )
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            r#"{"UpdateConfig":{"Delay":10000000000,"FailureAction":"pause","MaxFailureRatio":0.1,"Monitor":5000000000,"Order":"start-first","Parallelism":2}}"#,
+            options.serialize().unwrap()
+        );
+    }
+
+    #[test]
+    fn service_spec_labels() {
+        let spec: ServiceSpec = serde_json::from_str(
+            r#"{
+                "Name": "web",
+                "Labels": {"com.example.tier": "frontend"},
+                "TaskTemplate": {},
+                "Mode": {"Replicated": {"Replicas": 1}},
+                "EndpointSpec": {}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some("frontend"),
+            spec.labels
+                .as_ref()
+                .and_then(|labels| labels.get("com.example.tier"))
+                .map(String::as_str)
+        );
+    }
+
+    #[test]
+    fn task_info() {
+        let tasks: Vec<TaskInfo> = serde_json::from_str(
+            r#"[{
+                "ID": "0kzzo1i0y4jz6027t0k7aezc7",
+                "Version": {"Index": 71},
+                "CreatedAt": "2016-06-07T21:07:31.171892745Z",
+                "UpdatedAt": "2016-06-07T21:07:31.376370513Z",
+                "Spec": {},
+                "ServiceID": "9mnpnzenvg8p8tdbtq4wvbkcz",
+                "Slot": 1,
+                "NodeID": "60gvrl6tm78dmak4yl7srz94v",
+                "Status": {
+                    "Timestamp": "2016-06-07T21:07:31.290032978Z",
+                    "State": "running",
+                    "Message": "started",
+                    "ContainerStatus": {
+                        "ContainerID": "e5d62702a1b48d01c3e02ca1e0212a250801fa8d67caca0b6f35919ebc12f035",
+                        "PID": 677
+                    },
+                    "PortStatus": {}
+                },
+                "DesiredState": "running"
+            }]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some("60gvrl6tm78dmak4yl7srz94v"),
+            tasks[0].node_id.as_deref()
+        );
+        assert_eq!("running", tasks[0].status.state);
+    }
+
+    #[test]
+    fn service_info_is_converged() {
+        let mut info: ServiceInfo = serde_json::from_str(
+            r#"{
+                "ID": "9mnpnzenvg8p8tdbtq4wvbkcz",
+                "Version": {"Index": 19},
+                "CreatedAt": "2016-06-07T21:05:51.880065305Z",
+                "UpdatedAt": "2016-06-07T21:07:29.962229872Z",
+                "Endpoint": {"Spec": {}},
+                "ServiceStatus": {"RunningTasks": 1, "DesiredTasks": 2, "CompletedTasks": 0}
+            }"#,
+        )
+        .unwrap();
+        assert!(!info.is_converged());
+
+        info.service_status.as_mut().unwrap().running_tasks = 2;
+        assert!(info.is_converged());
+
+        info.service_status = None;
+        assert!(!info.is_converged());
+    }
+}
@@ -0,0 +1,375 @@
+//! Initialize, join and leave a swarm.
+//!
+//! API Reference: <https://docs.docker.com/engine/api/v1.41/#tag/Swarm>
+
+use std::collections::HashMap;
+
+use hyper::Body;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use url::form_urlencoded;
+
+use crate::{
+    docker::Docker,
+    errors::{Error, Result},
+    service::ObjectVersion,
+};
+
+#[cfg(feature = "chrono")]
+use chrono::{DateTime, Utc};
+
+/// Interface for the swarm the docker host is part of
+///
+/// API Reference: <https://docs.docker.com/engine/api/v1.41/#tag/Swarm>
+pub struct Swarm<'docker> {
+    docker: &'docker Docker,
+}
+
+impl<'docker> Swarm<'docker> {
+    /// Exports an interface for interacting with the swarm
+    pub fn new(docker: &'docker Docker) -> Self {
+        Swarm { docker }
+    }
+
+    /// Inspects the swarm
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/SwarmInspect>
+    pub async fn inspect(&self) -> Result<SwarmInfo> {
+        self.docker.get_json("/swarm").await
+    }
+
+    /// Initializes a new swarm, returning the id of the node
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/SwarmInit>
+    pub async fn init(
+        &self,
+        opts: &SwarmInitOptions,
+    ) -> Result<String> {
+        let body: Body = opts.serialize()?.into();
+
+        self.docker
+            .post_json("/swarm/init", Some((body, mime::APPLICATION_JSON)))
+            .await
+    }
+
+    /// Joins an existing swarm
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/SwarmJoin>
+    pub async fn join(
+        &self,
+        opts: &SwarmJoinOptions,
+    ) -> Result<()> {
+        let body: Body = opts.serialize()?.into();
+
+        self.docker
+            .post("/swarm/join", Some((body, mime::APPLICATION_JSON)))
+            .await?;
+        Ok(())
+    }
+
+    /// Leaves the swarm. `force` is required for a manager to leave, which may break the swarm.
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/SwarmLeave>
+    pub async fn leave(
+        &self,
+        force: bool,
+    ) -> Result<()> {
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("force", &force.to_string())
+            .finish();
+
+        self.docker
+            .post(&format!("/swarm/leave?{}", query), None)
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the key used to unlock the managers of an autolocked swarm
+    ///
+    /// API Reference: <https://docs.docker.com/engine/api/v1.41/#operation/SwarmUnlockkey>
+    pub async fn unlock_key(&self) -> Result<String> {
+        let key: UnlockKey = self.docker.get_json("/swarm/unlockkey").await?;
+        Ok(key.unlock_key)
+    }
+}
+
+/// Interface for initializing a swarm
+#[derive(Serialize, Debug)]
+pub struct SwarmInitOptions {
+    params: HashMap<&'static str, Value>,
+}
+
+impl SwarmInitOptions {
+    /// return a new instance of a builder for options
+    pub fn builder() -> SwarmInitOptionsBuilder {
+        SwarmInitOptionsBuilder::default()
+    }
+
+    /// serialize options as a string. returns None if no options are defined
+    pub fn serialize(&self) -> Result<String> {
+        serde_json::to_string(&self.params).map_err(Error::from)
+    }
+}
+
+#[derive(Default)]
+pub struct SwarmInitOptionsBuilder {
+    params: HashMap<&'static str, Value>,
+}
+
+impl SwarmInitOptionsBuilder {
+    /// Address used for inter-manager communication, e.g. `0.0.0.0:2377`
+    pub fn listen_addr(
+        &mut self,
+        addr: &str,
+    ) -> &mut Self {
+        self.params.insert("ListenAddr", json!(addr));
+        self
+    }
+
+    /// Address advertised to other nodes, either an address/port combination or an interface
+    pub fn advertise_addr(
+        &mut self,
+        addr: &str,
+    ) -> &mut Self {
+        self.params.insert("AdvertiseAddr", json!(addr));
+        self
+    }
+
+    /// Address or interface to use for data path traffic
+    pub fn data_path_addr(
+        &mut self,
+        addr: &str,
+    ) -> &mut Self {
+        self.params.insert("DataPathAddr", json!(addr));
+        self
+    }
+
+    /// Port number to use for data path traffic
+    pub fn data_path_port(
+        &mut self,
+        port: u32,
+    ) -> &mut Self {
+        self.params.insert("DataPathPort", json!(port));
+        self
+    }
+
+    /// Address pools, in CIDR format, from which global scope networks are allocated
+    pub fn default_addr_pool(
+        &mut self,
+        pool: Vec<&str>,
+    ) -> &mut Self {
+        self.params.insert("DefaultAddrPool", json!(pool));
+        self
+    }
+
+    /// Subnet size of the networks created from the default address pool
+    pub fn subnet_size(
+        &mut self,
+        size: u32,
+    ) -> &mut Self {
+        self.params.insert("SubnetSize", json!(size));
+        self
+    }
+
+    /// Force creation of a new swarm
+    pub fn force_new_cluster(
+        &mut self,
+        force: bool,
+    ) -> &mut Self {
+        self.params.insert("ForceNewCluster", json!(force));
+        self
+    }
+
+    /// Lock the managers' keys, requiring the [unlock key](Swarm::unlock_key) after a restart
+    pub fn autolock(
+        &mut self,
+        autolock: bool,
+    ) -> &mut Self {
+        self.params.insert(
+            "Spec",
+            json!({ "EncryptionConfig": { "AutoLockManagers": autolock } }),
+        );
+        self
+    }
+
+    pub fn build(&self) -> SwarmInitOptions {
+        SwarmInitOptions {
+            params: self.params.clone(),
+        }
+    }
+}
+
+/// Interface for joining a swarm
+#[derive(Serialize, Debug)]
+pub struct SwarmJoinOptions {
+    params: HashMap<&'static str, Value>,
+}
+
+impl SwarmJoinOptions {
+    /// return a new instance of a builder for options
+    pub fn builder(
+        join_token: &str,
+        remote_addrs: Vec<&str>,
+    ) -> SwarmJoinOptionsBuilder {
+        SwarmJoinOptionsBuilder::new(join_token, remote_addrs)
+    }
+
+    /// serialize options as a string. returns None if no options are defined
+    pub fn serialize(&self) -> Result<String> {
+        serde_json::to_string(&self.params).map_err(Error::from)
+    }
+}
+
+pub struct SwarmJoinOptionsBuilder {
+    params: HashMap<&'static str, Value>,
+}
+
+impl SwarmJoinOptionsBuilder {
+    pub(crate) fn new(
+        join_token: &str,
+        remote_addrs: Vec<&str>,
+    ) -> Self {
+        let mut params = HashMap::new();
+        params.insert("JoinToken", json!(join_token));
+        params.insert("RemoteAddrs", json!(remote_addrs));
+        SwarmJoinOptionsBuilder { params }
+    }
+
+    /// Address used for inter-manager communication if the node becomes a manager
+    pub fn listen_addr(
+        &mut self,
+        addr: &str,
+    ) -> &mut Self {
+        self.params.insert("ListenAddr", json!(addr));
+        self
+    }
+
+    /// Address advertised to other nodes, either an address/port combination or an interface
+    pub fn advertise_addr(
+        &mut self,
+        addr: &str,
+    ) -> &mut Self {
+        self.params.insert("AdvertiseAddr", json!(addr));
+        self
+    }
+
+    /// Address or interface to use for data path traffic
+    pub fn data_path_addr(
+        &mut self,
+        addr: &str,
+    ) -> &mut Self {
+        self.params.insert("DataPathAddr", json!(addr));
+        self
+    }
+
+    pub fn build(&self) -> SwarmJoinOptions {
+        SwarmJoinOptions {
+            params: self.params.clone(),
+        }
+    }
+}
+
+/// The swarm as reported by [Swarm::inspect](Swarm::inspect)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SwarmInfo {
+    #[serde(flatten)]
+    pub cluster: ClusterInfo,
+    pub join_tokens: JoinTokens,
+}
+
+/// The cluster of a swarm, also reported by managers in [Docker::info](crate::Docker::info)
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ClusterInfo {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub version: ObjectVersion,
+    #[cfg(feature = "chrono")]
+    pub created_at: DateTime<Utc>,
+    #[cfg(not(feature = "chrono"))]
+    pub created_at: String,
+    #[cfg(feature = "chrono")]
+    pub updated_at: DateTime<Utc>,
+    #[cfg(not(feature = "chrono"))]
+    pub updated_at: String,
+    pub spec: Option<Value>,
+    pub root_rotation_in_progress: bool,
+    pub data_path_port: Option<u32>,
+    pub default_addr_pool: Option<Vec<String>>,
+    pub subnet_size: Option<u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JoinTokens {
+    pub worker: String,
+    pub manager: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct UnlockKey {
+    unlock_key: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swarm_init_options() {
+        let options = SwarmInitOptions::builder()
+            .listen_addr("0.0.0.0:2377")
+            .advertise_addr("192.168.1.1")
+            .autolock(true)
+            .build();
+
+        assert_eq!(
+            json!({
+                "ListenAddr": "0.0.0.0:2377",
+                "AdvertiseAddr": "192.168.1.1",
+                "Spec": { "EncryptionConfig": { "AutoLockManagers": true } }
+            }),
+            serde_json::from_str::<Value>(&options.serialize().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn swarm_join_options() {
+        let options = SwarmJoinOptions::builder("SWMTKN-1-token", vec!["node1:2377"])
+            .listen_addr("0.0.0.0:2377")
+            .build();
+
+        assert_eq!(
+            json!({
+                "JoinToken": "SWMTKN-1-token",
+                "RemoteAddrs": ["node1:2377"],
+                "ListenAddr": "0.0.0.0:2377"
+            }),
+            serde_json::from_str::<Value>(&options.serialize().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn swarm_info() {
+        let info: SwarmInfo = serde_json::from_str(
+            r#"{
+                "ID": "abajmipo7b4xz5ip2nrla6b11",
+                "Version": {"Index": 373531},
+                "CreatedAt": "2016-08-18T10:44:24.496525531Z",
+                "UpdatedAt": "2017-08-09T07:09:37.632105588Z",
+                "RootRotationInProgress": false,
+                "DataPathPort": 4789,
+                "DefaultAddrPool": ["10.0.0.0/8"],
+                "SubnetSize": 24,
+                "JoinTokens": {"Worker": "SWMTKN-1-worker", "Manager": "SWMTKN-1-manager"}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!("abajmipo7b4xz5ip2nrla6b11", info.cluster.id);
+        assert_eq!(Some(4789), info.cluster.data_path_port);
+        assert_eq!("SWMTKN-1-worker", info.join_tokens.worker);
+    }
+}
@@ -1,71 +1,197 @@
 use flate2::{write::GzEncoder, Compression};
+use hyper::{body::Bytes, Body};
 use std::{
-    fs::{self, File},
-    io::{self, Write},
-    path::{Path, MAIN_SEPARATOR},
+    fs,
+    io::{self, BufWriter, Write},
+    path::Path,
+    thread,
 };
 use tar::Builder;
+use tokio::sync::mpsc;
 
-// todo: this is pretty involved. (re)factor this into its own crate
-pub fn dir<W>(
+/// Size of the chunks the archive is sent to the daemon in
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Number of chunks that may be buffered before tarring waits on the upload
+const CHUNKS_IN_FLIGHT: usize = 8;
+
+/// Writes a gzipped tarball of the directory at `path` to `buf`, archiving the targets of
+/// symlinks if `follow_symlinks` is set or the symlinks themselves otherwise
+pub fn dir_with<W>(
     buf: W,
     path: &str,
+    follow_symlinks: bool,
 ) -> io::Result<()>
 where
     W: Write,
 {
     let mut archive = Builder::new(GzEncoder::new(buf, Compression::best()));
-    fn bundle<F>(
+    archive.follow_symlinks(follow_symlinks);
+
+    fn bundle<W>(
+        archive: &mut Builder<W>,
+        root: &Path,
         dir: &Path,
-        f: &mut F,
-        bundle_dir: bool,
+        follow_symlinks: bool,
     ) -> io::Result<()>
     where
-        F: FnMut(&Path) -> io::Result<()>,
+        W: Write,
     {
-        if fs::metadata(dir)?.is_dir() {
-            if bundle_dir {
-                f(&dir)?;
-            }
-            for entry in fs::read_dir(dir)? {
-                let entry = entry?;
-                if fs::metadata(entry.path())?.is_dir() {
-                    bundle(&entry.path(), f, true)?;
-                } else {
-                    f(&entry.path().as_path())?;
-                }
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            // strip_prefix can't fail, all paths being read from below root
+            let relativized = path.strip_prefix(root).unwrap();
+            let metadata = if follow_symlinks {
+                fs::metadata(&path)?
+            } else {
+                fs::symlink_metadata(&path)?
+            };
+            if metadata.is_dir() {
+                archive.append_dir(relativized, &path)?;
+                bundle(archive, root, &path, follow_symlinks)?;
+            } else {
+                archive.append_path_with_name(&path, relativized)?;
             }
         }
         Ok(())
     }
 
-    {
-        let base_path = Path::new(path).canonicalize()?;
-        // todo: don't unwrap
-        let mut base_path_str = base_path.to_str().unwrap().to_owned();
-        if let Some(last) = base_path_str.chars().last() {
-            if last != MAIN_SEPARATOR {
-                base_path_str.push(MAIN_SEPARATOR)
-            }
+    let root = Path::new(path);
+    if fs::metadata(root)?.is_dir() {
+        bundle(&mut archive, root, root, follow_symlinks)?;
+    }
+    archive.into_inner()?.finish()?.flush()?;
+
+    Ok(())
+}
+
+/// Tars the directory at `path` on a separate thread, returning a `Body` that streams the
+/// archive as it is produced rather than buffering it in memory first
+pub(crate) fn dir_body(
+    path: String,
+    follow_symlinks: bool,
+) -> Body {
+    let (sender, receiver) = mpsc::channel(CHUNKS_IN_FLIGHT);
+
+    thread::spawn(move || {
+        let writer = BufWriter::with_capacity(CHUNK_SIZE, ChannelWriter(sender.clone()));
+        if let Err(e) = dir_with(writer, &path, follow_symlinks) {
+            let _ = sender.blocking_send(Err(e));
         }
+    });
+
+    async fn unfold(
+        mut receiver: mpsc::Receiver<io::Result<Bytes>>
+    ) -> Option<(io::Result<Bytes>, mpsc::Receiver<io::Result<Bytes>>)> {
+        let chunk = receiver.recv().await?;
+
+        Some((chunk, receiver))
+    }
+
+    Body::wrap_stream(futures_util::stream::unfold(receiver, unfold))
+}
 
-        let mut append = |path: &Path| {
-            let canonical = path.canonicalize()?;
-            // todo: don't unwrap
-            let relativized = canonical
-                .to_str()
+/// `Write` implementation which forwards everything written to it over a channel
+struct ChannelWriter(mpsc::Sender<io::Result<Bytes>>);
+
+impl Write for ChannelWriter {
+    fn write(
+        &mut self,
+        buf: &[u8],
+    ) -> io::Result<usize> {
+        self.0
+            .blocking_send(Ok(Bytes::copy_from_slice(buf)))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "archive receiver dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+
+    #[tokio::test]
+    async fn dir_body_streams_archive() {
+        let context = std::env::temp_dir().join(format!("shiplift-tarball-{}", std::process::id()));
+        fs::create_dir_all(context.join("nested")).unwrap();
+        fs::write(context.join("Dockerfile"), "FROM scratch\n").unwrap();
+        fs::write(context.join("nested").join("file"), "contents").unwrap();
+
+        let bytes = hyper::body::to_bytes(dir_body(context.to_string_lossy().into_owned(), true))
+            .await
+            .unwrap();
+        fs::remove_dir_all(&context).unwrap();
+
+        let mut archive = tar::Archive::new(GzDecoder::new(&bytes[..]));
+        let mut paths = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                entry
+                    .unwrap()
+                    .path()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+
+        assert_eq!(paths, vec!["Dockerfile", "nested", "nested/file"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dir_with_symlinks() {
+        let context =
+            std::env::temp_dir().join(format!("shiplift-symlinks-{}", std::process::id()));
+        fs::create_dir_all(context.join("vendor")).unwrap();
+        fs::write(context.join("vendor").join("lib"), "contents").unwrap();
+        std::os::unix::fs::symlink("vendor", context.join("linked")).unwrap();
+
+        let entries = |follow_symlinks| {
+            let mut data = Vec::new();
+            dir_with(&mut data, &context.to_string_lossy(), follow_symlinks).unwrap();
+            let mut archive = tar::Archive::new(GzDecoder::new(&data[..]));
+            let mut entries = archive
+                .entries()
                 .unwrap()
-                .trim_start_matches(&base_path_str[..]);
-            if path.is_dir() {
-                archive.append_dir(Path::new(relativized), &canonical)?
-            } else {
-                archive.append_file(Path::new(relativized), &mut File::open(&canonical)?)?
-            }
-            Ok(())
+                .map(|entry| {
+                    let entry = entry.unwrap();
+                    (
+                        entry.path().unwrap().to_string_lossy().into_owned(),
+                        entry.header().entry_type(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            entries
         };
-        bundle(Path::new(path), &mut append, false)?;
-    }
-    archive.finish()?;
+        let followed = entries(true);
+        let preserved = entries(false);
+        fs::remove_dir_all(&context).unwrap();
 
-    Ok(())
+        assert_eq!(
+            followed,
+            vec![
+                ("linked".to_owned(), tar::EntryType::Directory),
+                ("linked/lib".to_owned(), tar::EntryType::Regular),
+                ("vendor".to_owned(), tar::EntryType::Directory),
+                ("vendor/lib".to_owned(), tar::EntryType::Regular),
+            ]
+        );
+        assert_eq!(
+            preserved,
+            vec![
+                ("linked".to_owned(), tar::EntryType::Symlink),
+                ("vendor".to_owned(), tar::EntryType::Directory),
+                ("vendor/lib".to_owned(), tar::EntryType::Regular),
+            ]
+        );
+    }
 }
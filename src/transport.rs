@@ -8,11 +8,13 @@ use futures_util::{
 };
 use hyper::{
     body::Bytes,
-    client::{Client, HttpConnector},
+    client::{connect::Connect, Client, HttpConnector, ResponseFuture},
     header, Body, Method, Request, StatusCode,
 };
 #[cfg(feature = "tls")]
 use hyper_openssl::HttpsConnector;
+#[cfg(feature = "proxy")]
+use hyper_proxy::ProxyConnector;
 #[cfg(feature = "unix-socket")]
 use hyperlocal::UnixConnector;
 #[cfg(feature = "unix-socket")]
@@ -23,8 +25,13 @@ use serde::{Deserialize, Serialize};
 use std::{
     fmt, io, iter,
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
 };
+#[cfg(feature = "ssh")]
+use std::{future::Future, process::Stdio};
+#[cfg(feature = "ssh")]
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
 
 pub fn tar() -> Mime {
     "application/tar".parse().unwrap()
@@ -41,18 +48,47 @@ pub enum Transport {
     Tcp {
         client: Client<HttpConnector>,
         host: String,
+        /// Headers sent along with every request
+        headers: Vec<(&'static str, String)>,
+    },
+    /// A network tcp interface going through a client built by the caller, with any connector
+    Custom {
+        client: AnyClient,
+        host: String,
+        /// Headers sent along with every request
+        headers: Vec<(&'static str, String)>,
     },
     /// TCP/TLS
     #[cfg(feature = "tls")]
     EncryptedTcp {
         client: Client<HttpsConnector<HttpConnector>>,
         host: String,
+        /// Headers sent along with every request
+        headers: Vec<(&'static str, String)>,
+    },
+    /// A network tcp interface going through a HTTP proxy
+    #[cfg(feature = "proxy")]
+    Proxy {
+        client: Client<ProxyConnector<HttpConnector>>,
+        host: String,
+        /// Headers sent along with every request
+        headers: Vec<(&'static str, String)>,
     },
     /// A Unix domain socket
     #[cfg(feature = "unix-socket")]
     Unix {
         client: Client<UnixConnector>,
         path: String,
+        /// Headers sent along with every request
+        headers: Vec<(&'static str, String)>,
+    },
+    /// The socket of a remote docker host, tunneled over `ssh`
+    #[cfg(feature = "ssh")]
+    Ssh {
+        client: Client<SshConnector>,
+        destination: String,
+        /// Headers sent along with every request
+        headers: Vec<(&'static str, String)>,
     },
 }
 
@@ -63,15 +99,86 @@ impl fmt::Debug for Transport {
     ) -> fmt::Result {
         match *self {
             Transport::Tcp { ref host, .. } => write!(f, "Tcp({})", host),
+            Transport::Custom { ref host, .. } => write!(f, "Custom({})", host),
             #[cfg(feature = "tls")]
             Transport::EncryptedTcp { ref host, .. } => write!(f, "EncryptedTcp({})", host),
+            #[cfg(feature = "proxy")]
+            Transport::Proxy { ref host, .. } => write!(f, "Proxy({})", host),
             #[cfg(feature = "unix-socket")]
             Transport::Unix { ref path, .. } => write!(f, "Unix({})", path),
+            #[cfg(feature = "ssh")]
+            Transport::Ssh {
+                ref destination, ..
+            } => write!(f, "Ssh({})", destination),
         }
     }
 }
 
+/// A [Client](hyper::Client) with its connector type erased, e.g. one resolving names through a
+/// custom DNS resolver or connecting through a SOCKS proxy
+#[derive(Clone)]
+pub struct AnyClient(Arc<dyn Fn(Request<Body>) -> ResponseFuture + Send + Sync>);
+
+impl AnyClient {
+    pub fn new<C>(client: Client<C>) -> Self
+    where
+        C: Connect + Clone + Send + Sync + 'static,
+    {
+        AnyClient(Arc::new(move |req| client.request(req)))
+    }
+
+    fn request(
+        &self,
+        req: Request<Body>,
+    ) -> ResponseFuture {
+        (self.0)(req)
+    }
+}
+
 impl Transport {
+    /// Headers sent along with every request
+    pub fn default_headers(&self) -> &[(&'static str, String)] {
+        match *self {
+            Transport::Tcp { ref headers, .. } => headers,
+            Transport::Custom { ref headers, .. } => headers,
+            #[cfg(feature = "tls")]
+            Transport::EncryptedTcp { ref headers, .. } => headers,
+            #[cfg(feature = "proxy")]
+            Transport::Proxy { ref headers, .. } => headers,
+            #[cfg(feature = "unix-socket")]
+            Transport::Unix { ref headers, .. } => headers,
+            #[cfg(feature = "ssh")]
+            Transport::Ssh { ref headers, .. } => headers,
+        }
+    }
+
+    pub(crate) fn default_headers_mut(&mut self) -> &mut Vec<(&'static str, String)> {
+        match *self {
+            Transport::Tcp {
+                ref mut headers, ..
+            } => headers,
+            Transport::Custom {
+                ref mut headers, ..
+            } => headers,
+            #[cfg(feature = "tls")]
+            Transport::EncryptedTcp {
+                ref mut headers, ..
+            } => headers,
+            #[cfg(feature = "proxy")]
+            Transport::Proxy {
+                ref mut headers, ..
+            } => headers,
+            #[cfg(feature = "unix-socket")]
+            Transport::Unix {
+                ref mut headers, ..
+            } => headers,
+            #[cfg(feature = "ssh")]
+            Transport::Ssh {
+                ref mut headers, ..
+            } => headers,
+        }
+    }
+
     /// Make a request and return the whole response in a `String`
     pub async fn request<B, H>(
         &self,
@@ -128,6 +235,7 @@ impl Transport {
                             .unwrap_or("unknown error code")
                             .to_owned()
                     }),
+                    body: message_body,
                 })
             }
         }
@@ -178,13 +286,17 @@ impl Transport {
         H: IntoIterator<Item = (&'static str, String)>,
     {
         let req = match *self {
-            Transport::Tcp { ref host, .. } => {
+            Transport::Tcp { ref host, .. } | Transport::Custom { ref host, .. } => builder
+                .method(method)
+                .uri(&format!("{}{}", host, endpoint.as_ref())),
+            #[cfg(feature = "tls")]
+            Transport::EncryptedTcp { ref host, .. } => {
                 builder
                     .method(method)
                     .uri(&format!("{}{}", host, endpoint.as_ref()))
             }
-            #[cfg(feature = "tls")]
-            Transport::EncryptedTcp { ref host, .. } => {
+            #[cfg(feature = "proxy")]
+            Transport::Proxy { ref host, .. } => {
                 builder
                     .method(method)
                     .uri(&format!("{}{}", host, endpoint.as_ref()))
@@ -194,9 +306,20 @@ impl Transport {
                 let uri = DomainUri::new(&path, endpoint.as_ref());
                 builder.method(method).uri(uri)
             }
+            #[cfg(feature = "ssh")]
+            Transport::Ssh { .. } => {
+                // the connector ignores the host, which only needs to form a valid url
+                builder
+                    .method(method)
+                    .uri(&format!("http://docker{}", endpoint.as_ref()))
+            }
         };
         let mut req = req.header(header::HOST, "");
 
+        for (k, v) in self.default_headers() {
+            req = req.header(*k, v);
+        }
+
         if let Some(h) = headers {
             for (k, v) in h.into_iter() {
                 req = req.header(k, v);
@@ -218,10 +341,15 @@ impl Transport {
     ) -> Result<hyper::Response<Body>> {
         match self {
             Transport::Tcp { ref client, .. } => Ok(client.request(req).await?),
+            Transport::Custom { ref client, .. } => Ok(client.request(req).await?),
             #[cfg(feature = "tls")]
             Transport::EncryptedTcp { ref client, .. } => Ok(client.request(req).await?),
+            #[cfg(feature = "proxy")]
+            Transport::Proxy { ref client, .. } => Ok(client.request(req).await?),
             #[cfg(feature = "unix-socket")]
             Transport::Unix { ref client, .. } => Ok(client.request(req).await?),
+            #[cfg(feature = "ssh")]
+            Transport::Ssh { ref client, .. } => Ok(client.request(req).await?),
         }
     }
 
@@ -331,6 +459,130 @@ where
     }
 }
 
+/// Connects to the docker daemon of a remote host by running `docker system dial-stdio` over
+/// `ssh`, like the docker cli does for `ssh://` hosts. Authentication is left to the `ssh`
+/// client, e.g. its agent and config.
+#[cfg(feature = "ssh")]
+#[derive(Clone, Debug)]
+pub struct SshConnector {
+    destination: String,
+    port: Option<u16>,
+}
+
+#[cfg(feature = "ssh")]
+impl SshConnector {
+    /// Connects to `destination`, a `host` or `user@host`, on the given or default ssh port
+    pub fn new<S>(
+        destination: S,
+        port: Option<u16>,
+    ) -> Self
+    where
+        S: Into<String>,
+    {
+        SshConnector {
+            destination: destination.into(),
+            port,
+        }
+    }
+
+    fn command(&self) -> Command {
+        let mut command = Command::new("ssh");
+        if let Some(port) = self.port {
+            command.arg("-p").arg(port.to_string());
+        }
+        command
+            .arg("--")
+            .arg(&self.destination)
+            .args(["docker", "system", "dial-stdio"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true);
+        command
+    }
+}
+
+#[cfg(feature = "ssh")]
+impl hyper::service::Service<hyper::Uri> for SshConnector {
+    type Response = SshStream;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<SshStream>> + Send>>;
+
+    fn poll_ready(
+        &mut self,
+        _: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(
+        &mut self,
+        _: hyper::Uri,
+    ) -> Self::Future {
+        let mut command = self.command();
+        Box::pin(async move {
+            let mut child = command.spawn()?;
+            let missing = || io::Error::new(io::ErrorKind::BrokenPipe, "ssh stdio not captured");
+            let stdin = child.stdin.take().ok_or_else(missing)?;
+            let stdout = child.stdout.take().ok_or_else(missing)?;
+            Ok(SshStream {
+                _child: child,
+                stdin,
+                stdout,
+            })
+        })
+    }
+}
+
+/// A connection to the docker daemon through the stdio of an `ssh` process, which is killed
+/// when the connection is dropped
+#[cfg(feature = "ssh")]
+pub struct SshStream {
+    _child: Child,
+    stdin: ChildStdin,
+    stdout: ChildStdout,
+}
+
+#[cfg(feature = "ssh")]
+impl hyper::client::connect::Connection for SshStream {
+    fn connected(&self) -> hyper::client::connect::Connected {
+        hyper::client::connect::Connected::new()
+    }
+}
+
+#[cfg(feature = "ssh")]
+impl tokio::io::AsyncRead for SshStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut tokio::io::ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdout).poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "ssh")]
+impl tokio::io::AsyncWrite for SshStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stdin).poll_write(cx, buf)
+    }
+    fn poll_flush(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdin).poll_flush(cx)
+    }
+    fn poll_shutdown(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdin).poll_shutdown(cx)
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 struct ErrorResponse {
     message: String,
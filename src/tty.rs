@@ -2,7 +2,7 @@
 
 use crate::{Error, Result};
 use futures_util::{
-    io::{AsyncRead, AsyncReadExt, AsyncWrite},
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
     stream::{Stream, TryStreamExt},
 };
 use pin_project::pin_project;
@@ -57,7 +57,7 @@ where
 
     match stream.read_exact(&mut header_bytes).await {
         Err(e) if e.kind() == futures_util::io::ErrorKind::UnexpectedEof => return None,
-        Err(e) => return Some((Err(Error::IO(e)), stream)),
+        Err(e) => return Some((Err(unwrap_io_error(e)), stream)),
         _ => (),
     }
 
@@ -80,6 +80,16 @@ where
     Some((Ok(chunk), stream))
 }
 
+/// Recover the error of the underlying response, e.g. a `404` for an unknown container, which
+/// was wrapped to pass it through `AsyncRead`
+fn unwrap_io_error(e: io::Error) -> Error {
+    if matches!(e.get_ref(), Some(inner) if inner.is::<Error>()) {
+        *e.into_inner().unwrap().downcast::<Error>().unwrap()
+    } else {
+        Error::IO(e)
+    }
+}
+
 pub(crate) fn decode<S>(hyper_chunk_stream: S) -> impl Stream<Item = Result<TtyChunk>>
 where
     S: Stream<Item = Result<hyper::body::Bytes>> + Unpin,
@@ -91,8 +101,10 @@ where
     futures_util::stream::unfold(stream, decode_chunk)
 }
 
-type TtyReader<'a> = Pin<Box<dyn Stream<Item = Result<TtyChunk>> + Send + 'a>>;
-type TtyWriter<'a> = Pin<Box<dyn AsyncWrite + Send + 'a>>;
+/// The read half of a [Multiplexer](Multiplexer), streaming the container's output
+pub type TtyReader<'a> = Pin<Box<dyn Stream<Item = Result<TtyChunk>> + Send + 'a>>;
+/// The write half of a [Multiplexer](Multiplexer), streaming bytes to the container's stdin
+pub type TtyWriter<'a> = Pin<Box<dyn AsyncWrite + Send + 'a>>;
 
 /// TTY multiplexer returned by the `attach` method.
 ///
@@ -159,13 +171,51 @@ impl<'a> AsyncWrite for Multiplexer<'a> {
 }
 
 impl<'a> Multiplexer<'a> {
-    /// Split the `Multiplexer` into the component `Stream` and `AsyncWrite` parts
-    pub fn split(
-        self
-    ) -> (
-        impl Stream<Item = Result<TtyChunk>> + 'a,
-        impl AsyncWrite + Send + 'a,
-    ) {
+    /// Write `data` to the container's stdin and flush it
+    pub async fn send_stdin(
+        &mut self,
+        data: &[u8],
+    ) -> Result<()> {
+        self.writer.write_all(data).await?;
+        self.writer.flush().await?;
+        Ok(())
+    }
+
+    /// Write `line` followed by a newline to the container's stdin and flush it
+    pub async fn send_line(
+        &mut self,
+        line: &[u8],
+    ) -> Result<()> {
+        self.writer.write_all(line).await?;
+        self.send_stdin(b"\n").await
+    }
+
+    /// Split the `Multiplexer` into the component `Stream` and `AsyncWrite` parts.
+    ///
+    /// Either half can be dropped independently, e.g. to only feed the container's stdin.
+    pub fn split(self) -> (TtyReader<'a>, TtyWriter<'a>) {
         (self.reader, self.writer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+    use hyper::StatusCode;
+
+    #[test]
+    fn decode_surfaces_response_errors() {
+        let stream = futures_util::stream::iter(vec![Err(Error::Fault {
+            code: StatusCode::NOT_FOUND,
+            message: "No such container: typo".into(),
+            body: String::new(),
+        })]);
+        let mut chunks = Box::pin(decode(stream));
+
+        match futures::executor::block_on(chunks.next()) {
+            Some(Err(Error::Fault { code, .. })) => assert_eq!(StatusCode::NOT_FOUND, code),
+            other => panic!("expected a 404 fault, got {:?}", other),
+        }
+    }
+}
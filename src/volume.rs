@@ -210,6 +210,19 @@ pub struct VolumeInfo {
     pub mountpoint: String,
     pub options: Option<HashMap<String, String>>,
     pub scope: String,
+    /// Low-level details about the volume, provided by the volume driver
+    pub status: Option<HashMap<String, Value>>,
+    /// Only available when the volumes are listed with their disk usage
+    pub usage_data: Option<VolumeUsageData>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct VolumeUsageData {
+    /// Disk space used by the volume in bytes, or `-1` if not available
+    pub size: i64,
+    /// Number of containers referencing the volume, or `-1` if not available
+    pub ref_count: i64,
 }
 
 #[cfg(test)]
@@ -254,4 +267,25 @@ mod test {
         assert_eq!(volume_info.name, None);
         assert_eq!(volume_info.driver_opts, Some(driver_options))
     }
+
+    #[test]
+    fn volume_info_usage_data() {
+        let info: VolumeInfo = serde_json::from_str(
+            r#"{
+                "CreatedAt": "2016-06-07T20:31:11.853781916Z",
+                "Driver": "local",
+                "Labels": null,
+                "Name": "tardis",
+                "Mountpoint": "/var/lib/docker/volumes/tardis/_data",
+                "Options": null,
+                "Scope": "local",
+                "Status": {"hello": "world"},
+                "UsageData": {"Size": 10920104, "RefCount": 0}
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some(&json!("world")), info.status.unwrap().get("hello"));
+        assert_eq!(0, info.usage_data.unwrap().ref_count);
+    }
 }